@@ -0,0 +1,88 @@
+//! `--annotations-out`: OCI annotations for publishing the component.
+//!
+//! Registry UIs show an artifact's `org.opencontainers.image.*` annotations,
+//! so they're derived from the docs being injected. The file uses the
+//! layout `oras push --annotation-file` reads:
+//!
+//! ```json
+//! { "$manifest": { "org.opencontainers.image.title": "acme:shop", ... } }
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use wit_docs_inject::decode_package_docs;
+
+const TITLE: &str = "org.opencontainers.image.title";
+const VERSION: &str = "org.opencontainers.image.version";
+const DESCRIPTION: &str = "org.opencontainers.image.description";
+const AUTHORS: &str = "org.opencontainers.image.authors";
+const LICENSES: &str = "org.opencontainers.image.licenses";
+const URL: &str = "org.opencontainers.image.url";
+const SOURCE: &str = "org.opencontainers.image.source";
+
+/// The annotations for a component injected with the `package-docs` payload
+/// `package_docs` and the `package-docs-ext` JSON `ext`.
+///
+/// The title is the package name and the version its version. The
+/// description is the first paragraph of the package docs, else of the docs
+/// of the component's world (recorded with `--world`, or the only one).
+/// Authors, license, home page and repository come from the package
+/// metadata, when given.
+pub fn annotations(package_docs: &[u8], ext: &[u8]) -> Result<Value> {
+    let docs = decode_package_docs(package_docs)?.unwrap_or(Value::Null);
+    let ext: Value = serde_json::from_slice(ext).context("reading package-docs-ext")?;
+
+    let mut annotations = Map::new();
+    if let Some(package) = ext.get("package").and_then(|p| p.as_str()) {
+        let (name, version) = match package.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (package, None),
+        };
+        annotations.insert(TITLE.to_string(), json!(name));
+        if let Some(version) = version {
+            annotations.insert(VERSION.to_string(), json!(version));
+        }
+    }
+    if let Some(description) = description(&docs, &ext) {
+        annotations.insert(DESCRIPTION.to_string(), json!(description));
+    }
+    let metadata = &ext["metadata"];
+    if let Some(authors) = metadata.get("authors").and_then(|a| a.as_array()).filter(|a| !a.is_empty()) {
+        let authors: Vec<&str> = authors.iter().filter_map(|a| a.as_str()).collect();
+        annotations.insert(AUTHORS.to_string(), json!(authors.join(", ")));
+    }
+    for (key, annotation) in [("license", LICENSES), ("homepage", URL), ("repository", SOURCE)] {
+        if let Some(value) = metadata.get(key).and_then(|v| v.as_str()) {
+            annotations.insert(annotation.to_string(), json!(value));
+        }
+    }
+    Ok(json!({ "$manifest": annotations }))
+}
+
+/// The first paragraph of the package docs, else of the docs of the
+/// component's world.
+pub fn description(docs: &Value, ext: &Value) -> Option<String> {
+    let worlds = docs.get("worlds").and_then(|w| w.as_object());
+    let world = match ext.get("world").and_then(|w| w.as_str()) {
+        Some(world) => worlds.and_then(|w| w.get(world)),
+        None => worlds.filter(|w| w.len() == 1).and_then(|w| w.values().next()),
+    };
+    [docs.get("docs"), world.and_then(|w| w.get("docs"))]
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d.as_str())
+        .map(first_paragraph)
+        .find(|d| !d.is_empty())
+}
+
+/// First paragraph of a doc comment, on one line.
+fn first_paragraph(docs: &str) -> String {
+    docs.trim()
+        .split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,536 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::{Value, json};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+use wit_component::DecodedWasm;
+use tracing::{error, info, warn};
+use wit_docs_inject::{
+    AtomicFile, EXT_SECTION_NAME, Packaging, Verbosity, WorldSpec, collect_items, coverage, decode_package_docs,
+    find_custom_section, github_annotation, map_file, push_wit_path, qualify, source_location, verify_digest,
+};
+use wit_parser::{InterfaceId, PackageMetadata, Resolve, WorldItem, WorldKey};
+
+/// Check the documentation embedded in a WebAssembly component.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Path to the WebAssembly component (.wasm) file
+    component: PathBuf,
+
+    /// Compare the docs against the component's world: report docs for items
+    /// the component doesn't have, and exports that have no docs
+    #[arg(long)]
+    cross_validate: bool,
+
+    /// Documented world to compare against, e.g. `fetch` or
+    /// `ns:pkg/world@1.0.0` (default: the world recorded at injection time,
+    /// or the only documented world)
+    #[arg(long)]
+    world: Option<String>,
+
+    /// Compare the docs against a baseline file: report items documented in
+    /// the baseline that no longer are, and a drop in docs coverage
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record the current docs in the `--baseline` file when the check
+    /// passes (creating it if needed), so coverage can only ratchet up
+    #[arg(long, requires = "baseline")]
+    update_baseline: bool,
+
+    /// WIT package dir, or single .wit file, the docs were injected from:
+    /// report items whose docs in the sources no longer match the component's
+    #[arg(long, value_name = "WIT")]
+    against_wit: Option<PathBuf>,
+
+    /// How problems are reported
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Directory of the WIT sources, which the file names recorded with the
+    /// docs are relative to, for `--format github`
+    #[arg(long, value_name = "DIR", default_value = "wit")]
+    wit_dir: PathBuf,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum Format {
+    /// One `path: message` line per problem
+    Text,
+    /// GitHub Actions annotations on the WIT sources
+    Github,
+}
+
+/// Something wrong with the docs of one item.
+struct Problem {
+    /// Item path as used by `wit-docs-view` (`ns:pkg/world@1.0.0#func`, ...)
+    path: String,
+    message: String,
+}
+
+/// The items of the component's world, as decoded from its type information.
+#[derive(Default)]
+struct ComponentItems {
+    func_imports: Vec<String>,
+    func_exports: Vec<String>,
+    /// Interfaces imported under a plain name (inline interfaces in WIT)
+    interface_imports: BTreeMap<String, InterfaceId>,
+    interface_exports: BTreeMap<String, InterfaceId>,
+    /// Interfaces imported/exported by their `ns:pkg/iface@version` name
+    package_imports: BTreeMap<String, InterfaceId>,
+    package_exports: BTreeMap<String, InterfaceId>,
+    types: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let wasm_bytes = map_file(&args.component)
+        .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+
+    let Some(section) = find_custom_section(&wasm_bytes, PackageMetadata::SECTION_NAME)? else {
+        error!("No package-docs found in component");
+        std::process::exit(1);
+    };
+
+    let ext = match find_custom_section(&wasm_bytes, EXT_SECTION_NAME)? {
+        Some(ext) => serde_json::from_slice(ext.data).context("Failed to parse package-docs-ext JSON")?,
+        None => Value::Null,
+    };
+    // A section damaged on the way may still decode, or fail the schema
+    // check for reasons that would only confuse.
+    match verify_digest(section.data, &ext) {
+        Ok(true) => info!("package-docs matches its recorded digest"),
+        Ok(false) => info!("package-docs has no recorded digest; not verified"),
+        Err(err) => {
+            if args.format == Format::Github {
+                println!("{}", github_annotation("error", None, "wit-docs-check: digest", &err.to_string()));
+            }
+            return Err(err.into());
+        }
+    }
+
+    // The schema check is the one tools consuming the section will apply.
+    if let Err(err) = PackageMetadata::decode(section.data) {
+        let err = err.context("package-docs does not match the schema");
+        if args.format == Format::Github {
+            println!("{}", github_annotation("error", None, "wit-docs-check: schema", &format!("{:#}", err)));
+        }
+        return Err(err);
+    }
+    let docs = decode_package_docs(section.data)?.unwrap_or(Value::Null);
+
+    let mut problems = Vec::new();
+    if args.cross_validate {
+        let world = world_to_check(&docs, &ext, args.world.as_deref())?;
+        let (resolve, items) = decode_component(&wasm_bytes)?;
+        cross_validate(&docs, &ext, &world, &resolve, &items, &mut problems);
+    }
+    if let Some(wit) = &args.against_wit {
+        check_drift(&docs, &ext, wit, &mut problems).with_context(|| format!("Failed to read WIT {:?}", wit))?;
+    }
+    let current = args.baseline.as_ref().map(|_| snapshot(&wasm_bytes, &docs, &ext));
+    if let (Some(path), Some(current)) = (&args.baseline, &current) {
+        match read_baseline(path)? {
+            Some(baseline) => compare_baseline(&baseline, current, &docs, &mut problems),
+            None if args.update_baseline => info!("No baseline at {:?} yet; creating it", path),
+            None => anyhow::bail!("Baseline {:?} not found; create it with --update-baseline", path),
+        }
+    }
+
+    for problem in &problems {
+        match args.format {
+            Format::Text => println!("{}: {}", problem.path, problem.message),
+            Format::Github => {
+                let file = source_location(&ext, &problem.path).map(|(file, line)| (args.wit_dir.join(file), line));
+                println!(
+                    "{}",
+                    github_annotation(
+                        "error",
+                        file.as_ref().map(|(file, line)| (file.as_path(), *line)),
+                        "wit-docs-check",
+                        &format!("{}: {}", problem.path, problem.message),
+                    )
+                );
+            }
+        }
+    }
+    if !problems.is_empty() {
+        error!("{} problem(s) found", problems.len());
+        std::process::exit(1);
+    }
+    if let (true, Some(path), Some(current)) = (args.update_baseline, &args.baseline, &current) {
+        let mut file = AtomicFile::create(path).with_context(|| format!("writing {:?}", path))?;
+        writeln!(file.file(), "{}", serde_json::to_string_pretty(current)?)
+            .with_context(|| format!("writing {:?}", path))?;
+        file.commit().with_context(|| format!("replacing {:?}", path))?;
+        info!("Updated baseline {:?}", path);
+    }
+    info!("Docs OK");
+    Ok(())
+}
+
+/// What a baseline records of the docs: the package, its docs coverage
+/// (`null` when the component's WIT can't be read), and the paths of the
+/// items with docs.
+fn snapshot(wasm_bytes: &[u8], docs: &Value, ext: &Value) -> Value {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let covered = package.and_then(|package| {
+        coverage(wasm_bytes, docs, ext, package)
+            .map_err(|err| warn!("can't read the component's WIT ({:#}); no coverage in the baseline", err))
+            .ok()
+    });
+    let documented: BTreeSet<String> = collect_items(docs, package, None)
+        .into_iter()
+        .filter(|item| item.docs.as_deref().is_some_and(|d| !d.trim().is_empty()))
+        .map(|item| item.path)
+        .collect();
+    json!({
+        "package": package,
+        "coverage": covered.map(|c| c.to_json()),
+        "documented": documented,
+    })
+}
+
+/// The baseline at `path`, or `None` when there is no such file.
+fn read_baseline(path: &Path) -> Result<Option<Value>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read baseline {:?}", path)),
+    };
+    let baseline = serde_json::from_str(&text).with_context(|| format!("Failed to parse baseline {:?}", path))?;
+    Ok(Some(baseline))
+}
+
+/// Report regressions from `baseline` to the `current` snapshot: items that
+/// lost their docs, and lower coverage (e.g. from new undocumented items).
+fn compare_baseline(baseline: &Value, current: &Value, docs: &Value, problems: &mut Vec<Problem>) {
+    let package = current["package"].as_str();
+    let paths = |snapshot: &Value| -> BTreeSet<String> {
+        snapshot["documented"].as_array().into_iter().flatten().filter_map(|p| p.as_str().map(String::from)).collect()
+    };
+    let documented = paths(current);
+    let items: BTreeSet<String> = collect_items(docs, package, None).into_iter().map(|item| item.path).collect();
+    for path in paths(baseline).difference(&documented) {
+        let message = if items.contains(path) {
+            "lost its docs since the baseline"
+        } else {
+            "documented in the baseline, but no longer in the docs"
+        };
+        problems.push(Problem { path: path.clone(), message: message.to_string() });
+    }
+
+    let percent = |snapshot: &Value| snapshot["coverage"]["percent"].as_f64();
+    if let (Some(before), Some(now)) = (percent(baseline), percent(current))
+        && now < before
+    {
+        problems.push(Problem {
+            path: package.unwrap_or("package").to_string(),
+            message: format!("docs coverage fell from {}% to {}% since the baseline", before, now),
+        });
+    }
+}
+
+/// Report drift between the embedded docs and the docs the WIT sources at
+/// `path` have now: items whose docs changed, were added or were removed
+/// since the component was injected. Docs the injection made up
+/// (`--stub-missing`, `--generate-missing-cmd`) have no counterpart in the
+/// sources and are skipped, as are items of worlds and interfaces left out
+/// of the component's docs.
+fn check_drift(docs: &Value, ext: &Value, path: &Path, problems: &mut Vec<Problem>) -> Result<()> {
+    let mut resolve = Resolve::default();
+    let main = push_wit_path(&mut resolve, path)?;
+    // With `--world`, the docs may come from one of the `deps/`.
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let pkg = match package {
+        Some(package) => {
+            let name = package.split_once('@').map_or(package, |(name, _)| name);
+            let Some((id, found)) = resolve
+                .packages
+                .iter()
+                .find(|(_, p)| format!("{}:{}", p.name.namespace, p.name.name) == name)
+            else {
+                anyhow::bail!("package {} is not part of the WIT sources", name);
+            };
+            if found.name.to_string() != package {
+                problems.push(Problem {
+                    path: package.to_string(),
+                    message: format!("the WIT sources are package {}", found.name),
+                });
+            }
+            id
+        }
+        None => main,
+    };
+    let sources = serde_json::to_value(PackageMetadata::extract(&resolve, pkg))?;
+
+    let made_up: BTreeSet<&str> = [&ext["stubs"]["items"], &ext["generated"]]
+        .into_iter()
+        .filter_map(|paths| paths.as_array())
+        .flatten()
+        .filter_map(|path| path.as_str())
+        .collect();
+    let embedded: BTreeMap<String, Option<String>> =
+        collect_items(docs, package, None).into_iter().map(|item| (item.path, item.docs)).collect();
+    let containers: BTreeSet<String> = collect_items(docs, package, None)
+        .into_iter()
+        .map(|item| item.container.unwrap_or(item.path))
+        .collect();
+    let trimmed = |docs: &Option<String>| docs.as_deref().map(str::trim).filter(|d| !d.is_empty()).map(String::from);
+
+    let mut seen = BTreeSet::new();
+    for item in collect_items(&sources, package, None) {
+        let source = trimmed(&item.docs);
+        let message = match embedded.get(&item.path).map(trimmed) {
+            _ if made_up.contains(item.path.as_str()) => None,
+            Some(Some(embedded)) => match source {
+                Some(source) if source != embedded => Some("docs differ from the WIT sources"),
+                Some(_) => None,
+                None => Some("docs were removed from the WIT sources"),
+            },
+            Some(None) | None => {
+                let container = item.container.as_deref().unwrap_or(&item.path);
+                (source.is_some() && containers.contains(container))
+                    .then_some("documented in the WIT sources, but not in the component")
+            }
+        };
+        if let Some(message) = message {
+            problems.push(Problem { path: item.path.clone(), message: message.to_string() });
+        }
+        seen.insert(item.path);
+    }
+    for (path, docs) in &embedded {
+        if trimmed(docs).is_some() && !seen.contains(path) && !made_up.contains(path.as_str()) {
+            let message = "docs were removed from the WIT sources".to_string();
+            problems.push(Problem { path: path.clone(), message });
+        }
+    }
+    Ok(())
+}
+
+/// The world to cross-validate: the one given with `--world`, else the one
+/// recorded at injection time, else the only documented world.
+///
+/// Unlike for viewing, the world needn't have docs: a world without any still
+/// has exports to report.
+fn world_to_check(docs: &Value, ext: &Value, spec: Option<&str>) -> Result<String> {
+    if let Some(spec) = spec {
+        let world = WorldSpec::parse(spec);
+        if let Some(package) = ext.get("package").and_then(|p| p.as_str())
+            && !world.matches_package(package)
+        {
+            anyhow::bail!("World `{}` is not part of package `{}`", spec, package);
+        }
+        return Ok(world.name);
+    }
+    if let Some(world) = ext.get("world").and_then(|w| w.as_str()) {
+        return Ok(world.to_string());
+    }
+    let worlds: Vec<&String> = docs
+        .get("worlds")
+        .and_then(|w| w.as_object())
+        .into_iter()
+        .flat_map(|w| w.keys())
+        .collect();
+    match worlds.as_slice() {
+        [world] => Ok(world.to_string()),
+        [] => anyhow::bail!("No documented world; name the component's world with --world"),
+        _ => anyhow::bail!(
+            "Several documented worlds ({}); choose one with --world",
+            worlds.iter().map(|w| w.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+fn decode_component(wasm_bytes: &[u8]) -> Result<(Resolve, ComponentItems)> {
+    let decoded = wit_component::decode(wasm_bytes).context("Failed to decode component")?;
+    let DecodedWasm::Component(resolve, world) = decoded else {
+        anyhow::bail!("Expected a component, found a WIT package");
+    };
+
+    let mut items = ComponentItems::default();
+    let world = &resolve.worlds[world];
+    for (map, import) in [(&world.imports, true), (&world.exports, false)] {
+        for (key, item) in map {
+            let name = resolve.name_world_key(key);
+            match (key, item, import) {
+                (_, WorldItem::Function(_), true) => items.func_imports.push(name),
+                (_, WorldItem::Function(_), false) => items.func_exports.push(name),
+                (_, WorldItem::Type(_), _) => items.types.push(name),
+                (WorldKey::Name(_), WorldItem::Interface { id, .. }, true) => {
+                    items.interface_imports.insert(name, *id);
+                }
+                (WorldKey::Name(_), WorldItem::Interface { id, .. }, false) => {
+                    items.interface_exports.insert(name, *id);
+                }
+                (WorldKey::Interface(_), WorldItem::Interface { id, .. }, true) => {
+                    items.package_imports.insert(name, *id);
+                }
+                (WorldKey::Interface(_), WorldItem::Interface { id, .. }, false) => {
+                    items.package_exports.insert(name, *id);
+                }
+            }
+        }
+    }
+    Ok((resolve, items))
+}
+
+/// Report drift between the docs of `world_name` and the component's world.
+fn cross_validate(
+    docs: &Value,
+    ext: &Value,
+    world_name: &str,
+    resolve: &Resolve,
+    items: &ComponentItems,
+    problems: &mut Vec<Problem>,
+) {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let world_path = qualify(package, world_name);
+    let world = &docs["worlds"][world_name];
+
+    // Documented items the component doesn't have.
+    let func_maps = [("funcs", &items.func_imports, "imported"), ("func_exports", &items.func_exports, "exported")];
+    for (map, functions, direction) in func_maps {
+        for name in documented(world, map) {
+            if !functions.contains(name) {
+                problems.push(Problem {
+                    path: format!("{}#{}", world_path, name),
+                    message: format!("documented function is not {} by the component", direction),
+                });
+            }
+        }
+    }
+    let interface_maps = [
+        ("interfaces", &items.interface_imports, "imported"),
+        ("interface_exports", &items.interface_exports, "exported"),
+    ];
+    for (map, interfaces, direction) in interface_maps {
+        for name in documented(world, map) {
+            let path = format!("{}#{}", world_path, name);
+            match interfaces.get(name) {
+                Some(id) => stale_functions(&world[map][name], &format!("{}.", path), resolve, *id, problems),
+                None => problems.push(Problem {
+                    path,
+                    message: format!("documented interface is not {} by the component", direction),
+                }),
+            }
+        }
+    }
+    for name in documented(world, "types") {
+        if !items.types.contains(name) {
+            problems.push(Problem {
+                path: format!("{}#{}", world_path, name),
+                message: "documented type is not part of the component's world".to_string(),
+            });
+        }
+    }
+    // Interfaces of the documented package, where the component uses them.
+    for (name, id) in items.package_imports.iter().chain(&items.package_exports) {
+        if let Some(iface) = package_interface(name, package) {
+            let path = format!("{}#", name);
+            stale_functions(&docs["interfaces"][iface], &path, resolve, *id, problems);
+        }
+    }
+
+    // Exports without docs.
+    for name in &items.func_exports {
+        if !has_docs(&world["func_exports"][name]) {
+            problems.push(Problem {
+                path: format!("{}#{}", world_path, name),
+                message: "exported function has no docs".to_string(),
+            });
+        }
+    }
+    for (name, id) in &items.interface_exports {
+        let path = format!("{}#{}", world_path, name);
+        let iface_docs = &world["interface_exports"][name];
+        undocumented_functions(iface_docs, &path, &format!("{}.", path), resolve, *id, problems);
+    }
+    for (name, id) in &items.package_exports {
+        if let Some(iface) = package_interface(name, package) {
+            let iface_docs = &docs["interfaces"][iface];
+            undocumented_functions(iface_docs, name, &format!("{}#", name), resolve, *id, problems);
+        }
+    }
+}
+
+/// Names of the items documented in `container[map]`.
+fn documented<'a>(container: &'a Value, map: &str) -> impl Iterator<Item = &'a String> {
+    container.get(map).and_then(|m| m.as_object()).into_iter().flat_map(|m| m.keys())
+}
+
+/// The interface name, when `name` (`ns:pkg/iface@version`) belongs to the
+/// documented `package`.
+fn package_interface<'a>(name: &'a str, package: Option<&str>) -> Option<&'a str> {
+    let (path, version) = match name.split_once('@') {
+        Some((path, version)) => (path, Some(version)),
+        None => (name, None),
+    };
+    let (pkg, iface) = path.split_once('/')?;
+    let expected = match version {
+        Some(version) => format!("{}@{}", pkg, version),
+        None => pkg.to_string(),
+    };
+    (package? == expected).then_some(iface)
+}
+
+/// Functions documented on an interface that the component's version of it lacks.
+fn stale_functions(iface_docs: &Value, prefix: &str, resolve: &Resolve, id: InterfaceId, problems: &mut Vec<Problem>) {
+    let functions = &resolve.interfaces[id].functions;
+    for name in documented(iface_docs, "funcs") {
+        if !functions.contains_key(name) {
+            problems.push(Problem {
+                path: format!("{}{}", prefix, name),
+                message: "documented function is not part of the component's interface".to_string(),
+            });
+        }
+    }
+}
+
+/// An exported interface without docs, and its functions without docs.
+fn undocumented_functions(
+    iface_docs: &Value,
+    path: &str,
+    prefix: &str,
+    resolve: &Resolve,
+    id: InterfaceId,
+    problems: &mut Vec<Problem>,
+) {
+    if !has_docs(iface_docs) {
+        problems.push(Problem {
+            path: path.to_string(),
+            message: "exported interface has no docs".to_string(),
+        });
+    }
+    for name in resolve.interfaces[id].functions.keys() {
+        if !has_docs(&iface_docs["funcs"][name]) {
+            problems.push(Problem {
+                path: format!("{}{}", prefix, name),
+                message: "exported function has no docs".to_string(),
+            });
+        }
+    }
+}
+
+/// Whether a docs entry carries any text (functions may be bare strings).
+fn has_docs(item: &Value) -> bool {
+    item.as_str()
+        .or_else(|| item.get("docs").and_then(|d| d.as_str()))
+        .is_some_and(|d| !d.trim().is_empty())
+}
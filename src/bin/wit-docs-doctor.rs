@@ -0,0 +1,355 @@
+use anyhow::Result;
+use clap::Parser;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use wasmparser::{Encoding, Payload};
+use wit_component::DecodedWasm;
+use wit_docs_inject::{
+    COMPOSED_SECTION_NAME, DocsSection, EXT_SECTION_NAME, Packaging, Verbosity, decode_package_docs,
+    find_custom_sections, find_nested_docs, map_file, push_wit_path,
+};
+use wit_parser::{PackageMetadata, PackageName, Resolve, UnresolvedPackageGroup};
+
+/// Diagnose common problems with a component and the WIT its docs come from,
+/// and suggest fixes.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Component (.wasm) to check
+    component: Option<PathBuf>,
+
+    /// WIT package directory, or a single .wit file, to check
+    #[arg(long, visible_alias = "wit")]
+    wit_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+/// How bad a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Ok,
+    Note,
+    Problem,
+}
+
+/// The outcome of one check, with what to do about it.
+struct Finding {
+    severity: Severity,
+    message: String,
+    fix: Option<String>,
+}
+
+#[derive(Default)]
+struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn ok(&mut self, message: impl Into<String>) {
+        self.push(Severity::Ok, message.into(), None);
+    }
+
+    fn note(&mut self, message: impl Into<String>, fix: impl Into<String>) {
+        self.push(Severity::Note, message.into(), Some(fix.into()));
+    }
+
+    fn problem(&mut self, message: impl Into<String>, fix: impl Into<String>) {
+        self.push(Severity::Problem, message.into(), Some(fix.into()));
+    }
+
+    fn push(&mut self, severity: Severity, message: String, fix: Option<String>) {
+        self.findings.push(Finding { severity, message, fix });
+    }
+
+    fn print(&self) {
+        for finding in &self.findings {
+            let mark = match finding.severity {
+                Severity::Ok => "ok",
+                Severity::Note => "note",
+                Severity::Problem => "problem",
+            };
+            println!("{:>7}: {}", mark, finding.message);
+            if let Some(fix) = &finding.fix {
+                println!("    fix: {}", fix);
+            }
+        }
+    }
+
+    fn has_problems(&self) -> bool {
+        self.findings.iter().any(|f| f.severity == Severity::Problem)
+    }
+}
+
+fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(ExitCode::SUCCESS);
+    }
+    args.verbosity.init();
+    if args.component.is_none() && args.wit_dir.is_none() {
+        anyhow::bail!("nothing to check: give a component, --wit-dir, or both");
+    }
+
+    let mut report = Report::default();
+    let component = match &args.component {
+        Some(path) => check_component(path, &mut report),
+        None => None,
+    };
+    let wit = match &args.wit_dir {
+        Some(path) => check_wit(path, &mut report),
+        None => None,
+    };
+    if let (Some(component), Some(wit)) = (&component, &wit) {
+        check_versions(component, wit, &mut report);
+    }
+
+    report.print();
+    Ok(if report.has_problems() { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+/// What the component checks found out for the version checks.
+struct ComponentFacts {
+    /// Package the docs were injected from, as recorded in `package-docs-ext`
+    docs_package: Option<String>,
+    /// Versions of the packages the component imports and exports from
+    packages: BTreeMap<String, Option<String>>,
+}
+
+fn check_component(path: &Path, report: &mut Report) -> Option<ComponentFacts> {
+    let name = path.display();
+    let bytes = match map_file(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            report.problem(format!("{} can't be read: {}", name, err), "check the path and its permissions");
+            return None;
+        }
+    };
+    match wasmparser::Parser::new(0).parse_all(&bytes).next() {
+        Some(Ok(Payload::Version { encoding: Encoding::Component, .. })) => {}
+        Some(Ok(Payload::Version { encoding: Encoding::Module, .. })) => {
+            report.problem(
+                format!("{} is a core WebAssembly module, not a component", name),
+                "wrap it with `wasm-tools component new` (with a WASI adapter if it targets preview 1), \
+                 or build it with cargo-component, jco or componentize-py",
+            );
+            return None;
+        }
+        _ => {
+            report.problem(
+                format!("{} is not a WebAssembly file", name),
+                "pass the `.wasm` your toolchain built, not a WIT or text (.wat) file",
+            );
+            return None;
+        }
+    }
+    if let Some(err) = wasmparser::Parser::new(0).parse_all(&bytes).find_map(Result::err) {
+        report.problem(
+            format!("{} is malformed: {}", name, err),
+            "rebuild the component; `wasm-tools validate` shows where it breaks",
+        );
+        return None;
+    }
+    report.ok(format!("{} is a WebAssembly component", name));
+
+    let mut facts = ComponentFacts { docs_package: None, packages: BTreeMap::new() };
+    match wit_component::decode(&bytes) {
+        Ok(DecodedWasm::Component(resolve, _)) => {
+            for (_, package) in resolve.packages.iter() {
+                let key = format!("{}:{}", package.name.namespace, package.name.name);
+                facts.packages.insert(key, package.name.version.as_ref().map(|v| v.to_string()));
+            }
+        }
+        Ok(DecodedWasm::WitPackage(..)) => {
+            report.note(
+                format!("{} is a binary WIT package, not a component", name),
+                "to inject its docs into a component, pass it with `wit-docs-inject --wit-package`",
+            );
+        }
+        Err(err) => report.problem(
+            format!("the WIT of {} can't be decoded: {:#}", name, err),
+            "rebuild the component with an up-to-date toolchain",
+        ),
+    }
+
+    let sections = match find_custom_sections(&bytes, PackageMetadata::SECTION_NAME) {
+        Ok(sections) => sections,
+        Err(err) => {
+            report.problem(format!("{}'s custom sections can't be read: {:#}", name, err), "rebuild the component");
+            return Some(facts);
+        }
+    };
+    let exts = find_custom_sections(&bytes, EXT_SECTION_NAME).unwrap_or_default();
+    match sections.len() {
+        0 => {
+            let nested = find_nested_docs(&bytes).map(|n| n.len()).unwrap_or(0);
+            let composed = find_custom_sections(&bytes, COMPOSED_SECTION_NAME).is_ok_and(|s| !s.is_empty());
+            if composed {
+                report.ok(format!("{} carries the merged docs of its components", name));
+            } else if nested > 0 {
+                report.note(
+                    format!("{} has no docs of its own, but {} nested component(s) do", name, nested),
+                    "gather them with `wit-docs-inject --merge-nested`",
+                );
+            } else {
+                report.note(
+                    format!("{} has no package-docs section", name),
+                    "inject docs with `wit-docs-inject --component ... --wit-dir ...`",
+                );
+            }
+        }
+        1 => report.ok(format!("{} has a package-docs section", name)),
+        n if distinct_packages(&exts) == n => {
+            report.ok(format!("{} has package-docs sections for {} packages", name, n))
+        }
+        n => report.problem(
+            format!("{} has {} package-docs sections; toolchains read only the first", name, n),
+            "inject into the original component rather than an already injected one (`--out`, not `--inplace` \
+             twice); `wit-docs-view --section-index` shows each",
+        ),
+    }
+    for (index, section) in sections.iter().enumerate() {
+        match (section.data.first(), decode_package_docs(section.data)) {
+            (Some(version), _) if *version > 1 => report.problem(
+                format!("package-docs section {} has version {}, newer than this tool reads", index, version),
+                "update wit-docs-inject",
+            ),
+            (_, Err(err)) => report.problem(
+                format!("package-docs section {} can't be decoded: {:#}", index, err),
+                "re-inject the docs from the WIT sources",
+            ),
+            _ => {}
+        }
+    }
+    if !sections.is_empty() && exts.is_empty() {
+        report.note(
+            format!("{} has docs but no {} section; it was written by another tool", name, EXT_SECTION_NAME),
+            "re-inject with wit-docs-inject to record the package and world, which viewers use to match docs",
+        );
+    }
+    facts.docs_package = exts
+        .last()
+        .and_then(|ext| serde_json::from_slice::<Value>(ext.data).ok())
+        .and_then(|ext| ext.get("package")?.as_str().map(String::from));
+    Some(facts)
+}
+
+/// How many different packages the `package-docs-ext` sections record docs
+/// for.
+fn distinct_packages(exts: &[DocsSection]) -> usize {
+    let packages: BTreeSet<String> = exts
+        .iter()
+        .filter_map(|ext| serde_json::from_slice::<Value>(ext.data).ok())
+        .filter_map(|ext| ext.get("package")?.as_str().map(String::from))
+        .collect();
+    packages.len()
+}
+
+/// The WIT package and its dependencies, by `ns:pkg`, with their versions.
+struct WitFacts {
+    package: PackageName,
+    packages: BTreeMap<String, Option<String>>,
+}
+
+fn check_wit(path: &Path, report: &mut Report) -> Option<WitFacts> {
+    let name = path.display();
+    if !path.exists() {
+        report.problem(format!("{} doesn't exist", name), "pass the directory holding the package's .wit files");
+        return None;
+    }
+    if path.is_dir() {
+        let has_wit = std::fs::read_dir(path)
+            .map(|entries| entries.flatten().any(|e| e.path().extension().is_some_and(|x| x == "wit")))
+            .unwrap_or(false);
+        if !has_wit {
+            report.problem(
+                format!("{} has no .wit files", name),
+                "point --wit-dir at the package directory itself (often `wit/`), not at its parent",
+            );
+            return None;
+        }
+    }
+
+    let group = match UnresolvedPackageGroup::parse_path(path) {
+        Ok(group) => group,
+        Err(err) => {
+            report.problem(
+                format!("the WIT in {} doesn't parse: {:#}", name, err),
+                "fix the syntax error at the position shown; `wasm-tools component wit` reports the same",
+            );
+            return None;
+        }
+    };
+    report.ok(format!("{} parses as package {}", name, group.main.name));
+
+    let deps = match path.is_dir() {
+        true => path.join("deps"),
+        false => path.parent().unwrap_or(Path::new(".")).join("deps"),
+    };
+    let foreign: Vec<String> = group.main.foreign_deps.keys().map(|pkg| pkg.to_string()).collect();
+    if !foreign.is_empty() && !deps.is_dir() {
+        report.problem(
+            format!("the package uses {} but there is no {} folder", foreign.join(", "), deps.display()),
+            "fetch the dependencies into deps/, e.g. with `wkg wit fetch` or wit-deps",
+        );
+        return None;
+    }
+
+    let mut resolve = Resolve::default();
+    let pkg = match push_wit_path(&mut resolve, path) {
+        Ok(pkg) => pkg,
+        Err(err) => {
+            report.problem(
+                format!("the WIT in {} doesn't resolve: {:#}", name, err),
+                "add the missing packages to deps/, with the versions the package refers to",
+            );
+            return None;
+        }
+    };
+    report.ok(format!("{} resolves with {} dependencies", name, resolve.packages.len() - 1));
+
+    let packages = resolve
+        .packages
+        .iter()
+        .map(|(_, p)| (format!("{}:{}", p.name.namespace, p.name.name), p.name.version.as_ref().map(|v| v.to_string())))
+        .collect();
+    Some(WitFacts { package: resolve.packages[pkg].name.clone(), packages })
+}
+
+/// Compare the versions of the packages the component and the WIT use.
+fn check_versions(component: &ComponentFacts, wit: &WitFacts, report: &mut Report) {
+    let package = wit.package.to_string();
+    match &component.docs_package {
+        Some(docs) if *docs != package => report.problem(
+            format!("the component's docs were injected from {}, but the WIT is {}", docs, package),
+            "re-inject the docs from this WIT, or check out the WIT the component was built from",
+        ),
+        Some(_) => report.ok(format!("the component's docs come from {}", package)),
+        None => {}
+    }
+
+    let mut mismatched = false;
+    for (name, version) in &component.packages {
+        if let Some(wit_version) = wit.packages.get(name)
+            && wit_version != version
+        {
+            mismatched = true;
+            let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "no version".to_string());
+            report.problem(
+                format!("the component uses {} {}, but the WIT has {}", name, show(version), show(wit_version)),
+                "build the component and inject its docs from the same WIT and deps/",
+            );
+        }
+    }
+    if !mismatched {
+        report.ok("the component and the WIT agree on package versions");
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::{fs, path::PathBuf};
+use tracing::{error, info};
+use wit_docs_inject::{Error, Packaging, Verbosity, code_blocks, collect_items, map_file, read_docs};
+
+/// List the fenced code examples in the documentation of a WebAssembly component.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Path to the WebAssembly component (.wasm) file
+    component: PathBuf,
+
+    /// Write each example to a file in this directory instead of listing them
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Only examples whose fence names this language, e.g. `rust` or `wit`
+    #[arg(long)]
+    lang: Option<String>,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let wasm_bytes = map_file(&args.component)
+        .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+    let (docs, ext) = match read_docs(&wasm_bytes) {
+        Ok(docs) => docs,
+        Err(err @ Error::SectionMissing(_)) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let package = ext.get("package").and_then(|p| p.as_str());
+
+    if let Some(out_dir) = &args.out_dir {
+        fs::create_dir_all(out_dir).with_context(|| format!("creating {:?}", out_dir))?;
+    }
+    let mut count = 0;
+    for item in collect_items(&docs, package, None) {
+        let Some(text) = &item.docs else {
+            continue;
+        };
+        // Numbered before filtering, so names don't depend on `--lang`.
+        let blocks = code_blocks(text)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, block)| args.lang.as_ref().is_none_or(|lang| block.lang.eq_ignore_ascii_case(lang)));
+        for (index, block) in blocks {
+            count += 1;
+            match &args.out_dir {
+                Some(out_dir) => {
+                    let file = out_dir.join(format!(
+                        "{}-{}.{}",
+                        file_stem(&item.path),
+                        index + 1,
+                        extension(&block.lang)
+                    ));
+                    fs::write(&file, &block.code).with_context(|| format!("writing {:?}", file))?;
+                    println!("{}", file.display());
+                }
+                None => {
+                    let lang = if block.lang.is_empty() { "text" } else { block.lang.as_str() };
+                    println!("{} (example {}, {}):", item.path, index + 1, lang);
+                    for line in block.code.lines() {
+                        println!("    {}", line);
+                    }
+                    println!();
+                }
+            }
+        }
+    }
+
+    info!("{} example(s) found", count);
+    Ok(())
+}
+
+/// A file name for examples of the item at `path`: `acme:shop/orders@1.2.0#place`
+/// => `acme-shop-orders-1.2.0-place`.
+fn file_stem(path: &str) -> String {
+    let stem: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+        .collect();
+    stem.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+/// File extension for examples in `lang`, so tools pick them up by type.
+fn extension(lang: &str) -> String {
+    let lang = lang.to_ascii_lowercase();
+    let ext = match lang.as_str() {
+        "" | "text" | "txt" => "txt",
+        "rust" | "rs" => "rs",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "python" | "py" => "py",
+        "shell" | "sh" | "bash" | "console" => "sh",
+        "c++" | "cpp" => "cpp",
+        "csharp" | "c#" | "cs" => "cs",
+        "golang" | "go" => "go",
+        // `wit`, `json`, `toml`, ...
+        other if other.chars().all(|c| c.is_ascii_alphanumeric()) => other,
+        _ => "txt",
+    };
+    ext.to_string()
+}
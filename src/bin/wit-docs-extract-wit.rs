@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use clap::{ArgGroup, Parser};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+use tracing::{debug, info, warn};
+use wit_component::{DecodedWasm, WitPrinter};
+use wit_docs_inject::{
+    EXT_SECTION_NAME, Packaging, Verbosity, WIT_SECTION_NAME, decode_package_docs, find_custom_section, map_file,
+};
+use wit_parser::{
+    InterfaceId, PackageId, PackageMetadata, Resolve, TypeDefKind, TypeId, UnresolvedPackageGroup, WorldId,
+    WorldItem, WorldKey,
+};
+
+/// Reconstruct a WIT directory, with doc comments, from a WebAssembly
+/// component or binary WIT package.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+#[command(group(ArgGroup::new("output").required(true).multiple(true).args(["out_dir", "wasm"])))]
+struct Args {
+    /// Path to the WebAssembly component or WIT package (.wasm) file
+    component: PathBuf,
+
+    /// Directory to write the WIT files to; dependencies go to `deps/`
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Also write the WIT as a binary WIT package, with the main package's
+    /// docs in its `package-docs` section, for tools that take one file
+    /// (`wit-bindgen rust docs.wasm`)
+    #[arg(long, value_name = "FILE")]
+    wasm: Option<PathBuf>,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let wasm_bytes = map_file(&args.component)
+        .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+
+    let (resolve, main_pkg) = match find_custom_section(&wasm_bytes, WIT_SECTION_NAME)? {
+        // Text embedded with `--embed-wit` already has every doc comment.
+        Some(wit) => {
+            debug!("using the embedded WIT package");
+            let text = std::str::from_utf8(wit.data).context("package-docs-wit is not UTF-8")?;
+            let mut resolve = Resolve::default();
+            let group = UnresolvedPackageGroup::parse("package-docs-wit.wit", text)
+                .context("Failed to parse the embedded WIT")?;
+            let pkg = resolve.push_group(group).context("Failed to resolve the embedded WIT")?;
+            (resolve, pkg)
+        }
+        None => match wit_component::decode(&wasm_bytes).context("Failed to decode component")? {
+            // Binary WIT packages get their `package-docs` applied by the decoder.
+            DecodedWasm::WitPackage(resolve, pkg) => (resolve, pkg),
+            DecodedWasm::Component(mut resolve, world) => {
+                attach_docs(&wasm_bytes, &mut resolve, world)?;
+                let pkg = resolve.worlds[world].package.context("component world has no package")?;
+                (resolve, pkg)
+            }
+        },
+    };
+
+    if let Some(out_dir) = &args.out_dir {
+        write_packages(&resolve, main_pkg, out_dir)?;
+        info!("Wrote WIT to {:?}", out_dir);
+    }
+    if let Some(path) = &args.wasm {
+        // wit-component encodes the package's docs along with it.
+        let bytes = wit_component::encode(&resolve, main_pkg)
+            .with_context(|| format!("encoding WIT package {}", resolve.packages[main_pkg].name))?;
+        fs::write(path, bytes).with_context(|| format!("writing {:?}", path))?;
+        info!("Wrote WIT package to {:?}", path);
+    }
+    Ok(())
+}
+
+/// Re-attach the component's `package-docs` to the WIT decoded from its type
+/// information: the documented world's docs to the component's world, and
+/// interface docs to the interfaces of the documented package it uses.
+///
+/// A component only carries the parts of a package it uses, so docs for
+/// anything else are dropped rather than treated as an error.
+fn attach_docs(wasm_bytes: &[u8], resolve: &mut Resolve, world: WorldId) -> Result<()> {
+    let Some(section) = find_custom_section(wasm_bytes, PackageMetadata::SECTION_NAME)? else {
+        warn!("No package-docs found in component; writing WIT without docs");
+        return Ok(());
+    };
+    let docs = decode_package_docs(section.data)?.unwrap_or(Value::Null);
+    let ext = match find_custom_section(wasm_bytes, EXT_SECTION_NAME)? {
+        Some(ext) => serde_json::from_slice(ext.data).context("Failed to parse package-docs-ext JSON")?,
+        None => Value::Null,
+    };
+
+    // The decoded world is anonymous; it takes the name of the documented one.
+    let documented_world = match ext.get("world").and_then(|w| w.as_str()) {
+        Some(name) => Some(name.to_string()),
+        None => match docs.get("worlds").and_then(|w| w.as_object()) {
+            Some(worlds) if worlds.len() == 1 => worlds.keys().next().cloned(),
+            _ => None,
+        },
+    };
+    if let Some(name) = documented_world {
+        debug!(world = %name, "attaching world docs");
+        rename_world(resolve, world, &name);
+        attach_world(resolve, world, &docs["worlds"][&name]);
+    }
+
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let documented_pkg = resolve
+        .packages
+        .iter()
+        .find(|(_, pkg)| Some(pkg.name.to_string().as_str()) == package)
+        .map(|(id, _)| id);
+    if let Some(pkg) = documented_pkg {
+        debug!(package = ?package, "attaching interface docs");
+        resolve.packages[pkg].docs.contents = doc_text(&docs).map(String::from);
+        let interfaces: Vec<(String, InterfaceId)> =
+            resolve.packages[pkg].interfaces.iter().map(|(name, id)| (name.clone(), *id)).collect();
+        for (name, id) in interfaces {
+            attach_interface(resolve, id, &docs["interfaces"][&name]);
+        }
+    }
+    Ok(())
+}
+
+fn rename_world(resolve: &mut Resolve, world: WorldId, name: &str) {
+    let old = std::mem::replace(&mut resolve.worlds[world].name, name.to_string());
+    if let Some(pkg) = resolve.worlds[world].package {
+        let worlds = &mut resolve.packages[pkg].worlds;
+        worlds.shift_remove(&old);
+        worlds.insert(name.to_string(), world);
+    }
+}
+
+fn attach_world(resolve: &mut Resolve, id: WorldId, world_docs: &Value) {
+    resolve.worlds[id].docs.contents = doc_text(world_docs).map(String::from);
+    let mut interfaces = Vec::new();
+    let mut types = Vec::new();
+    let world = &mut resolve.worlds[id];
+    for (map, import) in [(&mut world.imports, true), (&mut world.exports, false)] {
+        let (funcs, ifaces) = match import {
+            true => ("funcs", "interfaces"),
+            false => ("func_exports", "interface_exports"),
+        };
+        for (key, item) in map.iter_mut() {
+            let WorldKey::Name(name) = key else {
+                continue;
+            };
+            match item {
+                WorldItem::Function(func) => {
+                    func.docs.contents = doc_text(&world_docs[funcs][name]).map(String::from);
+                }
+                WorldItem::Interface { id, .. } => interfaces.push((*id, &world_docs[ifaces][name])),
+                WorldItem::Type(ty) => types.push((*ty, &world_docs["types"][name])),
+            }
+        }
+    }
+    for (id, iface_docs) in interfaces {
+        attach_interface(resolve, id, iface_docs);
+    }
+    for (id, type_docs) in types {
+        attach_type(resolve, id, type_docs);
+    }
+}
+
+fn attach_interface(resolve: &mut Resolve, id: InterfaceId, iface_docs: &Value) {
+    let iface = &mut resolve.interfaces[id];
+    iface.docs.contents = doc_text(iface_docs).map(String::from);
+    for (name, func) in iface.functions.iter_mut() {
+        func.docs.contents = doc_text(&iface_docs["funcs"][name]).map(String::from);
+    }
+    let types: Vec<(TypeId, &Value)> =
+        iface.types.iter().map(|(name, id)| (*id, &iface_docs["types"][name])).collect();
+    for (id, type_docs) in types {
+        attach_type(resolve, id, type_docs);
+    }
+}
+
+fn attach_type(resolve: &mut Resolve, id: TypeId, type_docs: &Value) {
+    let ty = &mut resolve.types[id];
+    ty.docs.contents = doc_text(type_docs).map(String::from);
+    let items = &type_docs["items"];
+    let members = match &mut ty.kind {
+        TypeDefKind::Record(record) => record.fields.iter_mut().map(|f| (&f.name, &mut f.docs)).collect(),
+        TypeDefKind::Variant(variant) => variant.cases.iter_mut().map(|c| (&c.name, &mut c.docs)).collect(),
+        TypeDefKind::Enum(enum_) => enum_.cases.iter_mut().map(|c| (&c.name, &mut c.docs)).collect(),
+        TypeDefKind::Flags(flags) => flags.flags.iter_mut().map(|f| (&f.name, &mut f.docs)).collect(),
+        _ => Vec::new(),
+    };
+    for (name, docs) in members {
+        docs.contents = items[name.as_str()].as_str().map(String::from);
+    }
+}
+
+/// The docs of an item, which may be stored as a bare string.
+fn doc_text(item: &Value) -> Option<&str> {
+    item.as_str().or_else(|| item.get("docs").and_then(|d| d.as_str()))
+}
+
+/// Write `main_pkg` to `<out_dir>/<name>.wit` and every other package of
+/// `resolve` to `<out_dir>/deps/<ns>-<name>[-<version>]/package.wit`, the
+/// layout `wit-docs-inject --wit-dir` (and wit-parser) reads back.
+fn write_packages(resolve: &Resolve, main_pkg: PackageId, out_dir: &std::path::Path) -> Result<()> {
+    for (id, pkg) in resolve.packages.iter() {
+        let mut printer = WitPrinter::default();
+        printer
+            .print(resolve, id, &[])
+            .with_context(|| format!("printing WIT package {}", pkg.name))?;
+
+        let path = if id == main_pkg {
+            out_dir.join(format!("{}.wit", pkg.name.name))
+        } else {
+            let dir = match &pkg.name.version {
+                Some(version) => format!("{}-{}-{}", pkg.name.namespace, pkg.name.name, version),
+                None => format!("{}-{}", pkg.name.namespace, pkg.name.name),
+            };
+            out_dir.join("deps").join(dir).join("package.wit")
+        };
+        fs::create_dir_all(path.parent().unwrap_or(out_dir))
+            .with_context(|| format!("creating {:?}", path.parent()))?;
+        fs::write(&path, printer.output.to_string()).with_context(|| format!("writing {:?}", path))?;
+        debug!(package = %pkg.name, file = ?path, "wrote package");
+    }
+    Ok(())
+}
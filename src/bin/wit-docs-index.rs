@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::{Value, json};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+use wit_component::DecodedWasm;
+use wit_docs_inject::{
+    AtomicFile, EXT_SECTION_NAME, Packaging, Verbosity, coverage as docs_coverage, decode_package_docs,
+    find_custom_section, map_file, wasm_files,
+};
+use wit_parser::PackageMetadata;
+
+/// Write a manifest of the docs of many WebAssembly components: for each,
+/// its package, worlds, exports and how much of it is documented.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Components (.wasm files), or directories to index every component below
+    #[arg(required = true)]
+    components: Vec<PathBuf>,
+
+    /// Write the manifest here instead of to stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Name of the custom section holding the docs
+    #[arg(long, value_name = "NAME", default_value = PackageMetadata::SECTION_NAME)]
+    section_name: String,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let mut entries = Vec::new();
+    for input in &args.components {
+        if input.is_dir() {
+            let files = wasm_files(input).with_context(|| format!("Failed to list components in {:?}", input))?;
+            for file in files {
+                entries.push(index_entry(&file, &relative_name(&file, input), &args.section_name));
+            }
+        } else {
+            entries.push(index_entry(input, &input.display().to_string(), &args.section_name));
+        }
+    }
+    let documented = entries.iter().filter(|entry| !entry["package"].is_null()).count();
+    info!("Indexed {} component(s), {} with docs", entries.len(), documented);
+
+    let manifest = serde_json::to_string_pretty(&json!({ "components": entries }))?;
+    match &args.out {
+        Some(out) => {
+            let mut file = AtomicFile::create(out).with_context(|| format!("writing {:?}", out))?;
+            writeln!(file.file(), "{}", manifest).with_context(|| format!("writing {:?}", out))?;
+            file.commit().with_context(|| format!("replacing {:?}", out))?;
+        }
+        None => println!("{}", manifest),
+    }
+    Ok(())
+}
+
+/// `file`'s path below `dir`, with `/` separators on every platform.
+fn relative_name(file: &Path, dir: &Path) -> String {
+    file.strip_prefix(dir)
+        .unwrap_or(file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The manifest entry of one component. Components that can't be read or
+/// carry no docs are still listed, with a `null` package.
+fn index_entry(file: &Path, name: &str, section_name: &str) -> Value {
+    let mut entry = json!({
+        "component": name,
+        "package": null,
+        "worlds": [],
+        "exports": [],
+        "coverage": null,
+    });
+    let wasm_bytes = match map_file(file) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!("{}: {:#}", name, err);
+            entry["error"] = Value::String(format!("{:#}", err));
+            return entry;
+        }
+    };
+    match exports(&wasm_bytes) {
+        Ok(exports) => entry["exports"] = json!(exports),
+        Err(err) => warn!("{}: can't decode the component's exports: {:#}", name, err),
+    }
+    if let Err(err) = describe_docs(&wasm_bytes, section_name, &mut entry) {
+        warn!("{}: {:#}", name, err);
+        entry["error"] = Value::String(format!("{:#}", err));
+    }
+    entry
+}
+
+/// Fill in the package, worlds and coverage of `entry` from the component's
+/// docs, when it has any.
+fn describe_docs(wasm_bytes: &[u8], section_name: &str, entry: &mut Value) -> Result<()> {
+    let Some(section) = find_custom_section(wasm_bytes, section_name)? else {
+        info!("{}: no {}", entry["component"].as_str().unwrap_or_default(), section_name);
+        return Ok(());
+    };
+    let docs = decode_package_docs(section.data)?.unwrap_or(Value::Null);
+    let ext: Value = match find_custom_section(wasm_bytes, EXT_SECTION_NAME)? {
+        Some(ext) => serde_json::from_slice(ext.data).context("Failed to parse package-docs-ext JSON")?,
+        None => Value::Null,
+    };
+
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let (name, version) = match package.map(|p| p.split_once('@').unwrap_or((p, ""))) {
+        Some((name, version)) => (json!(name), if version.is_empty() { Value::Null } else { json!(version) }),
+        None => (Value::Null, Value::Null),
+    };
+    entry["package"] = json!({ "name": name, "version": version });
+    let worlds = docs.get("worlds").and_then(|w| w.as_object());
+    entry["worlds"] = json!(worlds.into_iter().flatten().map(|(world, _)| world).collect::<Vec<_>>());
+    if let Some(world) = ext.get("world") {
+        entry["world"] = world.clone();
+    }
+    let name = entry["component"].as_str().unwrap_or_default().to_string();
+    entry["coverage"] = coverage(wasm_bytes, &docs, &ext, &name).unwrap_or(Value::Null);
+    Ok(())
+}
+
+/// How many of the documented package's items known from the component's
+/// WIT have docs. `None` when the package wasn't recorded or the WIT can't
+/// be read.
+fn coverage(wasm_bytes: &[u8], docs: &Value, ext: &Value, name: &str) -> Option<Value> {
+    let package = ext.get("package").and_then(|p| p.as_str())?;
+    let coverage = docs_coverage(wasm_bytes, docs, ext, package)
+        .map_err(|err| warn!("{}: can't read the component's WIT ({:#}); no coverage", name, err))
+        .ok()?;
+    Some(coverage.to_json())
+}
+
+/// The names the component exports: functions by name, interfaces by their
+/// `ns:pkg/iface@version` name. A WIT package exports nothing.
+fn exports(wasm_bytes: &[u8]) -> Result<Vec<String>> {
+    match wit_component::decode(wasm_bytes)? {
+        DecodedWasm::WitPackage(..) => Ok(Vec::new()),
+        DecodedWasm::Component(resolve, world) => {
+            Ok(resolve.worlds[world].exports.keys().map(|key| resolve.name_world_key(key)).collect())
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info, warn};
+use unicase::UniCase;
+use wit_docs_inject::{
+    DocItem, Error, Packaging, Verbosity, code_blocks, collect_items, component_wit, documentable_items,
+    github_annotation, map_file, qualify, read_docs, source_location,
+};
+use wit_parser::UnresolvedPackageGroup;
+
+/// Lint the documentation embedded in a WebAssembly component.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Path to the WebAssembly component (.wasm) file
+    component: PathBuf,
+
+    /// Project dictionary: words the spell checker should accept, one per
+    /// line (`#` starts a comment)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    /// Rule configuration (default: `wit-docs.toml` in the current
+    /// directory, when there is one)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// How problems are reported
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Directory of the WIT sources, which the file names recorded with the
+    /// docs are relative to, for `--format github`
+    #[arg(long, value_name = "DIR", default_value = "wit")]
+    wit_dir: PathBuf,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum Format {
+    /// One `path: message [rule]` line per problem
+    Text,
+    /// GitHub Actions annotations on the WIT sources
+    Github,
+}
+
+/// A lint finding for one item.
+struct Problem {
+    /// Item path as used by `wit-docs-view` (`ns:pkg/world@1.0.0#func`, ...)
+    path: String,
+    /// Name of the rule that found it
+    rule: &'static str,
+    message: String,
+}
+
+/// How a rule's problems are reported.
+#[derive(Clone, Copy, PartialEq)]
+enum Level {
+    /// Reported, and fail the run
+    Error,
+    /// Reported only
+    Warn,
+    Off,
+}
+
+/// Every rule, with its level when not configured.
+const RULES: &[(&str, Level)] = &[("spelling", Level::Error), ("wit-fence", Level::Error), ("undocumented", Level::Off)];
+
+/// Configuration of one rule, from `[lint.rules.<rule>]` in `wit-docs.toml`.
+struct RuleConfig {
+    level: Level,
+    /// Item paths the rule ignores, along with everything inside them when
+    /// they name an interface or world
+    allow: Vec<String>,
+}
+
+/// The `[lint]` table of `wit-docs.toml`:
+///
+/// ```toml
+/// [lint]
+/// dictionary = "words.txt"
+///
+/// [lint.rules.undocumented]
+/// level = "warn"                       # "error", "warn" or "off"
+/// allow = ["legacy", "acme:shop/orders@1.2.0#refund"]
+/// ```
+struct Config {
+    /// Relative to the configuration file
+    dictionary: Option<PathBuf>,
+    rules: BTreeMap<&'static str, RuleConfig>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let wasm_bytes = map_file(&args.component)
+        .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+    let (docs, ext) = match read_docs(&wasm_bytes) {
+        Ok(docs) => docs,
+        Err(err @ Error::SectionMissing(_)) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let items = collect_items(&docs, package, None);
+
+    let config_path = args.config.clone().or_else(|| {
+        let default = PathBuf::from("wit-docs.toml");
+        default.exists().then_some(default)
+    });
+    let config = match &config_path {
+        Some(path) => load_config(path).with_context(|| format!("reading lint configuration from {:?}", path))?,
+        None => Config { dictionary: None, rules: BTreeMap::new() },
+    };
+    let rule = |name: &str| config.rules.get(name);
+    let enabled = |name: &str| {
+        let default = RULES.iter().find(|(rule, _)| *rule == name).map_or(Level::Off, |(_, level)| *level);
+        rule(name).map_or(default, |rule| rule.level) != Level::Off
+    };
+
+    let mut known_words = identifier_words(&items);
+    for dictionary in config.dictionary.iter().chain(&args.dictionary) {
+        let text = fs::read_to_string(dictionary).with_context(|| format!("reading {:?}", dictionary))?;
+        known_words.extend(dictionary_words(&text));
+    }
+    debug!(words = known_words.len(), "spell checker allowlist");
+
+    let mut problems = Vec::new();
+    for item in &items {
+        if let Some(text) = &item.docs {
+            if enabled("spelling") {
+                check_spelling(item, text, &known_words, &mut problems);
+            }
+            if enabled("wit-fence") {
+                check_wit_fences(item, text, &mut problems);
+            }
+        }
+    }
+    if enabled("undocumented") {
+        check_undocumented(&wasm_bytes, &docs, &ext, &items, &mut problems)?;
+    }
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    for problem in &problems {
+        let allowed = rule(problem.rule)
+            .is_some_and(|rule| rule.allow.iter().any(|entry| allows(entry, &problem.path, package)));
+        if allowed {
+            debug!(path = %problem.path, rule = problem.rule, "allowed: {}", problem.message);
+            continue;
+        }
+        let default = RULES.iter().find(|(rule, _)| *rule == problem.rule).map_or(Level::Error, |(_, level)| *level);
+        let warning = rule(problem.rule).map_or(default, |rule| rule.level) == Level::Warn;
+        if warning {
+            warnings += 1;
+        } else {
+            errors += 1;
+        }
+        match args.format {
+            Format::Text if warning => println!("{}: warning: {} [{}]", problem.path, problem.message, problem.rule),
+            Format::Text => println!("{}: {} [{}]", problem.path, problem.message, problem.rule),
+            Format::Github => {
+                let file = source_location(&ext, &problem.path).map(|(file, line)| (args.wit_dir.join(file), line));
+                println!(
+                    "{}",
+                    github_annotation(
+                        if warning { "warning" } else { "error" },
+                        file.as_ref().map(|(file, line)| (file.as_path(), *line)),
+                        &format!("wit-docs-lint: {}", problem.rule),
+                        &format!("{}: {}", problem.path, problem.message),
+                    )
+                );
+            }
+        }
+    }
+    if warnings > 0 {
+        warn!("{} warning(s)", warnings);
+    }
+    if errors > 0 {
+        error!("{} problem(s) found", errors);
+        std::process::exit(1);
+    }
+    info!("Docs OK");
+    Ok(())
+}
+
+fn load_config(path: &Path) -> Result<Config> {
+    let text = fs::read_to_string(path)?;
+    let table: toml::Table = toml::from_str(&text)?;
+    let mut config = Config { dictionary: None, rules: BTreeMap::new() };
+    let Some(lint) = table.get("lint") else {
+        return Ok(config);
+    };
+    let lint = lint.as_table().context("`lint` must be a table")?;
+    for (key, value) in lint {
+        match key.as_str() {
+            "dictionary" => {
+                let dictionary = value.as_str().context("`lint.dictionary` must be a path")?;
+                config.dictionary = Some(path.parent().unwrap_or(Path::new("")).join(dictionary));
+            }
+            "rules" => {
+                let rules = value.as_table().context("`lint.rules` must be a table")?;
+                for (name, value) in rules {
+                    let Some(&(rule, default)) = RULES.iter().find(|(rule, _)| rule == name) else {
+                        let known: Vec<&str> = RULES.iter().map(|(rule, _)| *rule).collect();
+                        anyhow::bail!("unknown lint rule `{}` (rules: {})", name, known.join(", "));
+                    };
+                    let rule_config = parse_rule_config(value, default).with_context(|| format!("in `lint.rules.{}`", name))?;
+                    config.rules.insert(rule, rule_config);
+                }
+            }
+            other => anyhow::bail!("unknown key `lint.{}`", other),
+        }
+    }
+    Ok(config)
+}
+
+fn parse_rule_config(value: &toml::Value, default: Level) -> Result<RuleConfig> {
+    let table = value.as_table().context("a rule's configuration must be a table")?;
+    let mut config = RuleConfig { level: default, allow: Vec::new() };
+    for (key, value) in table {
+        match key.as_str() {
+            "level" => {
+                config.level = match value.as_str() {
+                    Some("error") => Level::Error,
+                    Some("warn") => Level::Warn,
+                    Some("off") => Level::Off,
+                    _ => anyhow::bail!("`level` must be \"error\", \"warn\" or \"off\""),
+                }
+            }
+            "allow" => {
+                let entries = value.as_array().context("`allow` must be a list of item paths")?;
+                for entry in entries {
+                    config.allow.push(entry.as_str().context("`allow` must be a list of item paths")?.to_string());
+                }
+            }
+            other => anyhow::bail!("unknown key `{}`", other),
+        }
+    }
+    Ok(config)
+}
+
+/// Whether the allowlist `entry` covers the item at `path`: the item itself,
+/// or anything inside the interface or world it names. Entries naming an
+/// interface or world of the package may leave out the package
+/// (`orders#place` for `acme:shop/orders@1.2.0#place`).
+fn allows(entry: &str, path: &str, package: Option<&str>) -> bool {
+    let entry = match entry.split_once('#') {
+        _ if entry.contains(':') => entry.to_string(),
+        Some((name, item)) => format!("{}#{}", qualify(package, name), item),
+        None => qualify(package, entry),
+    };
+    path == entry
+        || path.strip_prefix(entry.as_str()).is_some_and(|rest| rest.starts_with('#') || rest.starts_with('.'))
+}
+
+/// Report the worlds, interfaces, functions and types of the documented
+/// package that have no docs. Items are taken from the embedded WIT when
+/// there is one; otherwise only those the component uses are known.
+fn check_undocumented(
+    wasm_bytes: &[u8],
+    docs: &Value,
+    ext: &Value,
+    items: &[DocItem],
+    problems: &mut Vec<Problem>,
+) -> Result<()> {
+    let Some(package) = ext.get("package").and_then(|p| p.as_str()) else {
+        warn!("the package isn't recorded in package-docs-ext; skipping the undocumented rule");
+        return Ok(());
+    };
+    let (resolve, component_world) = match component_wit(wasm_bytes) {
+        Ok(wit) => wit,
+        Err(err) => {
+            warn!("can't read the component's WIT ({:#}); skipping the undocumented rule", err);
+            return Ok(());
+        }
+    };
+    let documented: BTreeSet<&str> =
+        items.iter().filter(|item| item.docs.is_some()).map(|item| item.path.as_str()).collect();
+    let world_name = match ext.get("world").and_then(|w| w.as_str()) {
+        Some(world) => Some(world.to_string()),
+        None => match docs.get("worlds").and_then(|w| w.as_object()) {
+            Some(worlds) if worlds.len() == 1 => worlds.keys().next().cloned(),
+            _ => None,
+        },
+    };
+
+    for (path, kind) in documentable_items(&resolve, component_world, package, world_name.as_deref()) {
+        if !documented.contains(path.as_str()) {
+            problems.push(Problem { path, rule: "undocumented", message: format!("{} has no docs", kind) });
+        }
+    }
+    Ok(())
+}
+
+/// Words of the package's own identifiers (`place-order` => `place`,
+/// `order`), which docs naturally mention and are never typos.
+fn identifier_words(items: &[DocItem]) -> BTreeSet<String> {
+    items
+        .iter()
+        .flat_map(|item| item.path.split(|c: char| !c.is_ascii_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn dictionary_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+/// Report the common misspellings (as collected by the `typos` project) in
+/// the prose of `text`. Code spans, fenced code blocks and URLs are skipped,
+/// as are words that look like code (`camelCase`, `snake_case`, digits).
+fn check_spelling(item: &DocItem, text: &str, known_words: &BTreeSet<String>, problems: &mut Vec<Problem>) {
+    let mut reported = BTreeSet::new();
+    for word in prose_words(text) {
+        let word = word.strip_suffix("'s").unwrap_or(word);
+        if word.len() < 2 || known_words.contains(&word.to_lowercase()) {
+            continue;
+        }
+        let Some(corrections) = typos_dict::WORD.find(&UniCase::new(word)) else {
+            continue;
+        };
+        if !reported.insert(word.to_lowercase()) {
+            continue;
+        }
+        let message = match corrections {
+            [] => format!("`{}` is misspelled", word),
+            _ => format!("`{}` is misspelled; did you mean `{}`?", word, corrections.join("` or `")),
+        };
+        problems.push(Problem { path: item.path.clone(), rule: "spelling", message });
+    }
+}
+
+/// The words of the prose in `text`.
+fn prose_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        // Odd-numbered pieces between backticks are code spans.
+        for prose in line.split('`').step_by(2) {
+            for token in prose.split_whitespace() {
+                if token.contains("://") || token.starts_with("www.") {
+                    continue;
+                }
+                let code_like = token.contains(|c: char| c == '_' || c.is_ascii_digit())
+                    || token.chars().skip(1).any(|c| c.is_uppercase());
+                if code_like {
+                    continue;
+                }
+                words.extend(
+                    token
+                        .split(|c: char| !(c.is_alphabetic() || c == '\''))
+                        .map(|word| word.trim_matches('\''))
+                        .filter(|word| !word.is_empty()),
+                );
+            }
+        }
+    }
+    words
+}
+
+/// Report ```` ```wit ```` blocks that don't parse.
+///
+/// Snippets are often fragments: one lacking `package`, `interface` or
+/// `world` is parsed as the body of an interface (or, when it starts with
+/// `import`, `export` or `include`, of a world). References to names the
+/// snippet doesn't define are accepted, since they usually live elsewhere in
+/// the package.
+fn check_wit_fences(item: &DocItem, text: &str, problems: &mut Vec<Problem>) {
+    for (index, block) in code_blocks(text).iter().enumerate() {
+        if !block.lang.eq_ignore_ascii_case("wit") {
+            continue;
+        }
+        let Err(err) = parse_wit_snippet(&block.code) else {
+            continue;
+        };
+        let err = format!("{:#}", err);
+        // The error's first line, with the location made relative to the docs.
+        let message = err.lines().next().unwrap_or_default().trim_end_matches(':').to_string();
+        let unknown_name = ["is not defined", "does not exist", "not found in package"];
+        if unknown_name.iter().any(|m| message.contains(m)) {
+            debug!(path = %item.path, "ignoring unresolved name in WIT example: {}", message);
+            continue;
+        }
+        let line = err
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("--> "))
+            .and_then(|loc| loc.split(':').nth(1)?.parse::<usize>().ok());
+        let message = match line {
+            Some(line) => format!(
+                "WIT example {} doesn't parse (docs line {}): {}",
+                index + 1,
+                block.line + line - 1,
+                message
+            ),
+            None => format!("WIT example {} doesn't parse: {}", index + 1, message),
+        };
+        problems.push(Problem { path: item.path.clone(), rule: "wit-fence", message });
+    }
+}
+
+/// Parse a WIT snippet, wrapping fragments in a world or interface and
+/// supplying a package header where missing. The snippet always starts on
+/// line 2 (after the wrapper, the header, or an empty line), so
+/// line `n` of an error is line `n - 1` of the snippet.
+fn parse_wit_snippet(code: &str) -> Result<UnresolvedPackageGroup> {
+    let first_word = code
+        .lines()
+        .map(|l| l.split("//").next().unwrap_or_default().trim())
+        .find(|l| !l.is_empty())
+        .and_then(|l| l.split_whitespace().next())
+        .unwrap_or_default();
+    const PACKAGE: &str = "package example:snippet;";
+    let source = match first_word.trim_start_matches('@').split('(').next().unwrap_or_default() {
+        "package" => format!("\n{}", code),
+        "interface" | "world" | "since" | "unstable" | "deprecated" => format!("{}\n{}", PACKAGE, code),
+        "import" | "export" | "include" => format!("{} world snippet {{\n{}}}\n", PACKAGE, code),
+        _ => format!("{} interface snippet {{\n{}}}\n", PACKAGE, code),
+    };
+    UnresolvedPackageGroup::parse("example.wit", &source)
+}
@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::{borrow::Cow, io::Write, path::PathBuf};
+use tracing::{error, info, warn};
+use wasm_encoder::{ComponentSection, CustomSection};
+use wasmparser::{Chunk, Payload};
+use wit_docs_inject::{
+    AtomicFile, PACKAGE_DOCS_VERSION, Packaging, Verbosity, encode_package_docs, map_file, normalize_docs,
+};
+use wit_parser::PackageMetadata;
+
+/// Rewrite legacy `package-docs` sections of a WebAssembly component to the
+/// current schema.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Path to the WebAssembly component (.wasm) file
+    component: PathBuf,
+
+    /// Write the migrated component here; without `--out` or `--inplace`,
+    /// only report what would change
+    #[arg(long, conflicts_with = "inplace")]
+    out: Option<PathBuf>,
+
+    /// Overwrite the input file with the migrated component
+    #[arg(long)]
+    inplace: bool,
+
+    /// Name of the custom section holding the docs
+    #[arg(long, value_name = "NAME", default_value = PackageMetadata::SECTION_NAME)]
+    section_name: String,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let input = map_file(&args.component)
+        .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+    let (output, changes, sections) = migrate(&input, &args.section_name)?;
+    drop(input);
+
+    if sections == 0 {
+        error!("No {} found in component", args.section_name);
+        std::process::exit(1);
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+    if changes.is_empty() {
+        info!("{} already uses the current schema", args.section_name);
+        return Ok(());
+    }
+
+    let out = match (&args.out, args.inplace) {
+        (Some(out), _) => out,
+        (None, true) => &args.component,
+        (None, false) => {
+            info!("{} change(s) needed; write them with --out or --inplace", changes.len());
+            return Ok(());
+        }
+    };
+    let mut file = AtomicFile::create(out).with_context(|| format!("writing {:?}", out))?;
+    file.file().write_all(&output).with_context(|| format!("writing {:?}", out))?;
+    file.commit().with_context(|| format!("replacing {:?}", out))?;
+    info!("Migrated {} in {:?}", args.section_name, out);
+    Ok(())
+}
+
+/// Copy `wasm_bytes`, rewriting each top-level `section_name` section in
+/// place. Returns the new bytes, the changes made, and the number of docs
+/// sections found.
+fn migrate(wasm_bytes: &[u8], section_name: &str) -> Result<(Vec<u8>, Vec<String>, usize)> {
+    let mut parser = wasmparser::Parser::new(0);
+    let mut output = Vec::with_capacity(wasm_bytes.len());
+    let mut changes = Vec::new();
+    let mut sections = 0;
+    let mut offset = 0;
+
+    loop {
+        let (payload, consumed) = match parser.parse(&wasm_bytes[offset..], true)? {
+            Chunk::Parsed { payload, consumed } => (payload, consumed),
+            Chunk::NeedMoreData(_) => anyhow::bail!("component is truncated"),
+        };
+        let start = offset;
+        offset += consumed;
+        match payload {
+            // Nested modules and components are copied whole: their docs
+            // belong to them. The parser resumes after them.
+            Payload::ModuleSection { unchecked_range, .. } | Payload::ComponentSection { unchecked_range, .. } => {
+                offset += unchecked_range.len();
+            }
+            Payload::CustomSection(reader) if reader.name() == section_name => {
+                sections += 1;
+                match migrate_section(reader.data()) {
+                    Ok(Some((data, section_changes))) => {
+                        let section = CustomSection {
+                            name: Cow::Borrowed(section_name),
+                            data: Cow::Owned(data),
+                        };
+                        section.append_to_component(&mut output);
+                        changes.extend(section_changes);
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(err) => warn!("leaving {} section {} as is: {:#}", section_name, sections, err),
+                }
+            }
+            _ => {}
+        }
+        output.extend_from_slice(&wasm_bytes[start..offset]);
+        if offset >= wasm_bytes.len() {
+            break;
+        }
+    }
+
+    Ok((output, changes, sections))
+}
+
+/// The section's data in the current format, with the changes made, or
+/// `None` when it is already current.
+fn migrate_section(data: &[u8]) -> Result<Option<(Vec<u8>, Vec<String>)>> {
+    let Some((&version, json)) = data.split_first() else {
+        anyhow::bail!("section is empty");
+    };
+    let mut docs: serde_json::Value = serde_json::from_slice(json).context("Failed to parse package-docs JSON")?;
+    let mut changes = normalize_docs(&mut docs);
+    match version {
+        PACKAGE_DOCS_VERSION => {}
+        0 => changes.push(format!("version {} format upgraded to version {}", version, PACKAGE_DOCS_VERSION)),
+        _ => anyhow::bail!("unknown package-docs version {}", version),
+    }
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    // Whatever else is left would still be rejected by toolchains.
+    let migrated =
+        encode_package_docs(&docs).context("migrated docs still don't match the current package-docs schema")?;
+    Ok(Some((migrated, changes)))
+}
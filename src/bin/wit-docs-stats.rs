@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+use wit_docs_inject::{
+    AtomicFile, COMPOSED_SECTION_NAME, Coverage, EXT_SECTION_NAME, Packaging, Verbosity, WIT_SECTION_NAME,
+    collect_items, coverage, decode_package_docs, find_custom_section, find_custom_sections, map_file, wasm_files,
+};
+use wit_parser::PackageMetadata;
+
+/// Summarize the docs of many WebAssembly components: coverage, item
+/// counts, deprecated items and docs section sizes, in total and per
+/// component.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Components (.wasm files), or directories to include every component below
+    #[arg(required = true)]
+    components: Vec<PathBuf>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "json")]
+    format: Format,
+
+    /// Write the report here instead of to stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Name of the custom section holding the docs
+    #[arg(long, value_name = "NAME", default_value = PackageMetadata::SECTION_NAME)]
+    section_name: String,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
+}
+
+#[derive(Debug, Clone, PartialEq, clap::ValueEnum)]
+enum Format {
+    Json,
+    Markdown,
+}
+
+/// The docs statistics of one component.
+#[derive(Default)]
+struct Stats {
+    component: String,
+    has_docs: bool,
+    /// `ns:pkg@version`, as recorded by `wit-docs-inject`
+    package: Option<String>,
+    /// `None` for components without docs, or whose WIT can't be read
+    coverage: Option<Coverage>,
+    /// Items with an entry in the docs, and how many of those have docs
+    items: usize,
+    documented: usize,
+    deprecated: usize,
+    /// Bytes of each docs section, by section name
+    section_bytes: BTreeMap<String, usize>,
+    error: Option<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
+
+    let mut stats = Vec::new();
+    for input in &args.components {
+        if input.is_dir() {
+            let files = wasm_files(input).with_context(|| format!("Failed to list components in {:?}", input))?;
+            for file in files {
+                stats.push(component_stats(&file, &relative_name(&file, input), &args.section_name));
+            }
+        } else {
+            stats.push(component_stats(input, &input.display().to_string(), &args.section_name));
+        }
+    }
+    info!("Read {} component(s), {} with docs", stats.len(), stats.iter().filter(|s| s.has_docs).count());
+
+    let report = match args.format {
+        Format::Json => serde_json::to_string_pretty(&json!({
+            "summary": summary_json(&stats),
+            "components": stats.iter().map(stats_json).collect::<Vec<_>>(),
+        }))?,
+        Format::Markdown => markdown(&stats),
+    };
+    match &args.out {
+        Some(out) => {
+            let mut file = AtomicFile::create(out).with_context(|| format!("writing {:?}", out))?;
+            writeln!(file.file(), "{}", report).with_context(|| format!("writing {:?}", out))?;
+            file.commit().with_context(|| format!("replacing {:?}", out))?;
+        }
+        None => println!("{}", report),
+    }
+    Ok(())
+}
+
+/// `file`'s path below `dir`, with `/` separators on every platform.
+fn relative_name(file: &Path, dir: &Path) -> String {
+    file.strip_prefix(dir)
+        .unwrap_or(file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The statistics of one component. Components that can't be read are
+/// still counted, with an error.
+fn component_stats(file: &Path, name: &str, section_name: &str) -> Stats {
+    let mut stats = Stats { component: name.to_string(), ..Default::default() };
+    let wasm_bytes = match map_file(file) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!("{}: {:#}", name, err);
+            stats.error = Some(format!("{:#}", err));
+            return stats;
+        }
+    };
+    if let Err(err) = describe_docs(&wasm_bytes, section_name, &mut stats) {
+        warn!("{}: {:#}", name, err);
+        stats.error = Some(format!("{:#}", err));
+    }
+    stats
+}
+
+fn describe_docs(wasm_bytes: &[u8], section_name: &str, stats: &mut Stats) -> Result<()> {
+    for section in [section_name, EXT_SECTION_NAME, WIT_SECTION_NAME, COMPOSED_SECTION_NAME] {
+        let bytes: usize = find_custom_sections(wasm_bytes, section)?.iter().map(|s| s.data.len()).sum();
+        if bytes > 0 {
+            stats.section_bytes.insert(section.to_string(), bytes);
+        }
+    }
+
+    let Some(section) = find_custom_section(wasm_bytes, section_name)? else {
+        info!("{}: no {}", stats.component, section_name);
+        return Ok(());
+    };
+    let docs = decode_package_docs(section.data)?.unwrap_or(Value::Null);
+    let ext: Value = match find_custom_section(wasm_bytes, EXT_SECTION_NAME)? {
+        Some(ext) => serde_json::from_slice(ext.data).context("Failed to parse package-docs-ext JSON")?,
+        None => Value::Null,
+    };
+
+    let package = ext.get("package").and_then(|p| p.as_str());
+    stats.has_docs = true;
+    stats.package = package.map(String::from);
+    let items = collect_items(&docs, package, None);
+    stats.items = items.len();
+    stats.documented = items.iter().filter(|item| item.docs.is_some()).count();
+    stats.deprecated = deprecated(&docs);
+    if let Some(package) = package {
+        stats.coverage = coverage(wasm_bytes, &docs, &ext, package)
+            .map_err(|err| warn!("{}: can't read the component's WIT ({:#}); no coverage", stats.component, err))
+            .ok();
+    }
+    Ok(())
+}
+
+/// How many items of a docs payload are marked deprecated: those with a
+/// `stability` (`{"stable": {"since": ..., "deprecated": ...}}`) that
+/// records a deprecation.
+fn deprecated(value: &Value) -> usize {
+    let Value::Object(object) = value else {
+        return 0;
+    };
+    let own = object
+        .get("stability")
+        .and_then(|s| s.as_object())
+        .is_some_and(|s| s.values().any(|v| v.get("deprecated").is_some_and(|d| !d.is_null())));
+    let nested: usize = object.iter().filter(|(key, _)| *key != "stability").map(|(_, v)| deprecated(v)).sum();
+    usize::from(own) + nested
+}
+
+/// The coverage of all components with a known coverage taken together.
+fn total_coverage(stats: &[Stats]) -> Coverage {
+    let covered = stats.iter().filter_map(|s| s.coverage.as_ref());
+    covered.fold(Coverage { documented: 0, total: 0 }, |sum, c| Coverage {
+        documented: sum.documented + c.documented,
+        total: sum.total + c.total,
+    })
+}
+
+fn total_section_bytes(stats: &[Stats]) -> BTreeMap<&str, usize> {
+    let mut totals = BTreeMap::new();
+    for (section, bytes) in stats.iter().flat_map(|s| &s.section_bytes) {
+        *totals.entry(section.as_str()).or_default() += bytes;
+    }
+    totals
+}
+
+fn summary_json(stats: &[Stats]) -> Value {
+    json!({
+        "components": stats.len(),
+        "with_docs": stats.iter().filter(|s| s.has_docs).count(),
+        "errors": stats.iter().filter(|s| s.error.is_some()).count(),
+        "coverage": total_coverage(stats).to_json(),
+        "items": {
+            "total": stats.iter().map(|s| s.items).sum::<usize>(),
+            "documented": stats.iter().map(|s| s.documented).sum::<usize>(),
+        },
+        "deprecated": stats.iter().map(|s| s.deprecated).sum::<usize>(),
+        "section_bytes": total_section_bytes(stats),
+    })
+}
+
+fn stats_json(stats: &Stats) -> Value {
+    let mut entry = json!({
+        "component": stats.component,
+        "package": stats.package,
+        "coverage": stats.coverage.as_ref().map(Coverage::to_json),
+        "items": { "total": stats.items, "documented": stats.documented },
+        "deprecated": stats.deprecated,
+        "section_bytes": stats.section_bytes,
+    });
+    if let Some(error) = &stats.error {
+        entry["error"] = json!(error);
+    }
+    entry
+}
+
+fn markdown(stats: &[Stats]) -> String {
+    let coverage = total_coverage(stats);
+    let mut out = String::from("# Documentation Statistics\n\n");
+    let _ = writeln!(out, "- Components: {}", stats.len());
+    let _ = writeln!(out, "- With docs: {}", stats.iter().filter(|s| s.has_docs).count());
+    let errors = stats.iter().filter(|s| s.error.is_some()).count();
+    if errors > 0 {
+        let _ = writeln!(out, "- Unreadable: {}", errors);
+    }
+    let _ = writeln!(out, "- Coverage: {}% ({} of {} items)", coverage.percent(), coverage.documented, coverage.total);
+    let _ = writeln!(out, "- Deprecated items: {}", stats.iter().map(|s| s.deprecated).sum::<usize>());
+    for (section, bytes) in total_section_bytes(stats) {
+        let _ = writeln!(out, "- `{}`: {} bytes", section, bytes);
+    }
+
+    out.push_str("\n## Components\n\n");
+    out.push_str("| Component | Package | Coverage | Documented | Deprecated | Docs bytes |\n");
+    out.push_str("|---|---|---|---|---|---|\n");
+    for s in stats {
+        let coverage = match (&s.coverage, &s.error) {
+            (_, Some(_)) => "error".to_string(),
+            (Some(c), None) => format!("{}%", c.percent()),
+            (None, None) => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "| `{}` | {} | {} | {} / {} | {} | {} |",
+            s.component,
+            s.package.as_deref().map_or("-".to_string(), |p| format!("`{}`", p)),
+            coverage,
+            s.documented,
+            s.items,
+            s.deprecated,
+            s.section_bytes.values().sum::<usize>(),
+        );
+    }
+    out.trim_end().to_string()
+}
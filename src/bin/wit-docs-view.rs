@@ -1,27 +1,196 @@
 use anyhow::{Context, Result};
 use clap::Parser;
-use serde_json::Value;
-use std::{fs, path::PathBuf, process::Command};
-use wasmparser::{Parser as WasmParser, Payload};
+use pulldown_cmark::{Event, Tag};
+use serde_json::{Value, json};
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+};
+use tracing::{debug, error, info, warn};
+use wit_docs_inject::{
+    AtomicFile, COMPOSED_SECTION_NAME, DocItem, DocsSection, EXT_SECTION_NAME, Error as DocsError, NestedComponent,
+    Packaging, WIT_SECTION_NAME, WorldSpec, collect_items, component_wit, decode_package_docs, find_key,
+    same_versioned_name, doc_text, find_custom_section, find_custom_sections, find_nested_docs, hide_progress,
+    function_signature, init_logging, map_file, nested_components, normalize_name, qualify, resource_of,
+    select_world, verify_digest, wasm_files,
+};
+use wit_component::DecodedWasm;
+use wit_parser::{
+    InterfaceId, PackageMetadata, Resolve, TypeDefKind, TypeId, WorldId, WorldItem, WorldKey,
+};
+
+thread_local! {
+    /// Output collected instead of written to stdout, while [`capture`] runs.
+    static CAPTURED: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Like `print!`, but captured by [`capture`].
+macro_rules! out {
+    ($($arg:tt)*) => {
+        emit(&format!($($arg)*))
+    };
+}
+
+/// Like `println!`, but captured by [`capture`].
+macro_rules! outln {
+    () => {
+        emit("\n")
+    };
+    ($($arg:tt)*) => {
+        emit(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
 
 /// View documentation from a WebAssembly component's `package-docs` custom section.
-#[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[derive(Parser, Debug, Clone)]
+#[command(author, version, about, after_help = EXIT_CODES_HELP)]
 struct Args {
-    /// Path to the WebAssembly component (.wasm) file
+    /// Path to the WebAssembly component (.wasm) file, or a directory to
+    /// view every component below it
     component: PathBuf,
 
     /// Output format
     #[arg(long, value_enum, default_value = "pretty")]
     format: OutputFormat,
 
-    /// Show only function documentation
+    /// Name of the custom section to read the docs from, for components
+    /// written with `wit-docs-inject --section-name`
+    #[arg(long, value_name = "NAME", default_value = PackageMetadata::SECTION_NAME)]
+    section_name: String,
+
+    /// Of several docs sections, show the one at this position (from 0, in
+    /// file order)
+    #[arg(long, value_name = "N", conflicts_with = "package")]
+    section_index: Option<usize>,
+
+    /// Of several docs sections, show the one documenting this package
+    /// (`ns:pkg`, optionally with `@version`)
+    #[arg(long)]
+    package: Option<String>,
+
+    /// Show the docs of a component nested in a composed one: its index in
+    /// `--list-subcomponents`, its name, or the package it documents
+    #[arg(long, value_name = "INDEX|NAME")]
+    subcomponent: Option<String>,
+
+    /// List the components nested in a composed one, with their names and
+    /// the packages they document
+    #[arg(long, conflicts_with_all = ["subcomponent", "item", "out_dir", "hexdump"])]
+    list_subcomponents: bool,
+
+    /// Show only function documentation (combines with the other `--*-only` flags)
     #[arg(long)]
     functions_only: bool,
 
     /// Show only world documentation
     #[arg(long)]
     worlds_only: bool,
+
+    /// Show only types, with their fields and cases
+    #[arg(long)]
+    types_only: bool,
+
+    /// Show only interfaces
+    #[arg(long)]
+    interfaces_only: bool,
+
+    /// Show only resources and their constructors, methods and static functions
+    #[arg(long)]
+    resources_only: bool,
+
+    /// Show only what the component imports: the API a host must provide
+    #[arg(long, conflicts_with = "exports_only")]
+    imports_only: bool,
+
+    /// Show only what the component exports: the API consumers can call
+    #[arg(long)]
+    exports_only: bool,
+
+    /// Print an annotated hex dump of the section instead of decoding it
+    #[arg(long)]
+    hexdump: bool,
+
+    /// Write the output to a file in this directory, named after the
+    /// component (`<name>.json`, `<name>.md`, ...), instead of stdout; with
+    /// `--format all`, one file per format from a single decoding
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["hexdump", "item"])]
+    out_dir: Option<PathBuf>,
+
+    /// Only show this world, e.g. `fetch` or `ns:pkg/world@1.0.0`; also used
+    /// to match docs against the component's WIT in `--format wit`
+    #[arg(long)]
+    world: Option<String>,
+
+    /// JSON file of URL templates for linking `ns:pkg/iface` references in
+    /// markdown, keyed by `ns:pkg` or `ns` (extends the built-in `wasi` links)
+    #[arg(long)]
+    link_map: Option<PathBuf>,
+
+    /// Show the WIT file and line each doc comment came from, when recorded
+    #[arg(long)]
+    show_source: bool,
+
+    /// List items grouped by the interface or world they belong to, by
+    /// direction (exports, imports, definitions), or by kind
+    #[arg(long, value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Order items by path, or by where their docs appear in the WIT
+    /// sources (needs source locations, see `--show-source`)
+    #[arg(long, value_enum)]
+    sort: Option<SortOrder>,
+
+    /// With `--format markdown` or `html`, list each interface's and world's
+    /// functions as a table (name, signature, summary) instead of a section
+    /// each
+    #[arg(long)]
+    table: bool,
+
+    /// When the component has no docs, show its API decoded from its type
+    /// information instead, every item marked as undocumented
+    #[arg(long, conflicts_with = "quiet")]
+    allow_missing: bool,
+
+    /// When to use colors: for WIT syntax in `--format wit` and rendered
+    /// markdown in pretty output (`auto`: when writing to a terminal and
+    /// `NO_COLOR` isn't set)
+    #[arg(long, value_enum, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Print doc comments as written in pretty output, instead of rendering
+    /// their markdown (emphasis, lists, code spans) for the terminal
+    #[arg(long)]
+    plain: bool,
+
+    /// Show only the first sentence of each doc comment in pretty output,
+    /// followed by `…` when there is more
+    #[arg(long)]
+    summary: bool,
+
+    /// Wrap doc comments in pretty output to this many columns (default:
+    /// the terminal's width when writing to one; 0 never wraps)
+    #[arg(long, value_name = "COLUMNS")]
+    width: Option<usize>,
+
+    /// Show just this item, e.g. `wasi:http/outgoing-handler#handle`: its
+    /// signature and docs (as one object with `--format json` or `ndjson`)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["group_by", "sort", "table", "hexdump"])]
+    item: Option<String>,
+
+    /// Log more: `-v` for debug output, `-vv` for everything
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Print nothing; only the exit code tells whether docs were found
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    #[command(flatten)]
+    packaging: Packaging,
 }
 
 #[derive(Debug, Clone, PartialEq, clap::ValueEnum)]
@@ -30,199 +199,2580 @@ enum OutputFormat {
     Json,
     Markdown,
     Wit,
+    /// Exact section bytes (version byte + payload), written unmodified
+    Raw,
+    /// JSON array of `{path, kind, summary}` for client-side search
+    SearchIndex,
+    /// One `{path, kind, signature, docs}` JSON object per line and item,
+    /// for jq and streaming ingestion
+    Ndjson,
+    /// TypeScript (ESM) module exporting the docs with their types, for jco hosts
+    Ts,
+    /// Package, worlds, interfaces and items as an indented tree with
+    /// one-line summaries
+    Tree,
+    /// The markdown output as a standalone HTML page
+    Html,
+    /// JSON, markdown and HTML at once, written to `--out-dir`
+    All,
+    /// Markdown pages with front matter and a sidebar for a Docusaurus
+    /// site, written to `--out-dir`
+    Docusaurus,
+    /// Markdown pages with front matter and a nav file for an MkDocs site,
+    /// written to `--out-dir`
+    Mkdocs,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    
-    let wasm_bytes = fs::read(&args.component)
+impl OutputFormat {
+    /// File extension for `--out-dir`.
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Pretty | OutputFormat::Tree => "txt",
+            OutputFormat::Json | OutputFormat::SearchIndex => "json",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Wit => "wit",
+            OutputFormat::Raw => "bin",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Ts => "ts",
+            OutputFormat::Html => "html",
+            OutputFormat::All => unreachable!("--format all is split into its formats"),
+            OutputFormat::Docusaurus | OutputFormat::Mkdocs => unreachable!("sites are written page by page"),
+        }
+    }
+
+    /// Whether the format writes the pages of a docs site.
+    fn is_site(&self) -> bool {
+        matches!(self, OutputFormat::Docusaurus | OutputFormat::Mkdocs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum GroupBy {
+    Interface,
+    Direction,
+    Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum SortOrder {
+    Name,
+    SourceOrder,
+}
+
+/// URL templates for references to WIT packages and interfaces, keyed by
+/// `ns:pkg` or by namespace alone. Templates may use `{namespace}`, `{name}`,
+/// `{interface}` and `{version}`.
+type LinkMap = BTreeMap<String, String>;
+
+/// Exit codes, for scripts that test for docs. Success is 0.
+const EXIT_NO_DOCS: u8 = 1;
+const EXIT_DECODE_ERROR: u8 = 2;
+const EXIT_IO_ERROR: u8 = 3;
+/// Everything else: invalid arguments, an unknown `--world`, ...
+const EXIT_OTHER_ERROR: u8 = 4;
+/// The docs have no item at the `--item` path.
+const EXIT_NO_ITEM: u8 = 5;
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  Docs found (and shown)
+  1  No docs section, or an empty one
+  2  The component or its docs don't decode, or don't match their recorded digest
+  3  I/O error, e.g. the component can't be read
+  4  Any other error: invalid arguments, an unknown --world, ...
+  5  No item at the --item path";
+
+fn main() -> ExitCode {
+    // clap's own exit code for bad arguments (2) would read as a decode error.
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(err) if err.use_stderr() => {
+            let _ = err.print();
+            return ExitCode::from(EXIT_OTHER_ERROR);
+        }
+        Err(err) => err.exit(),
+    };
+    if args.quiet {
+        hide_progress();
+    } else {
+        init_logging(args.verbose, false);
+    }
+    match args.packaging.handle::<Args>(env!("CARGO_BIN_NAME")) {
+        Ok(true) => return ExitCode::SUCCESS,
+        Ok(false) => {}
+        Err(err) => {
+            error!("{:#}", err);
+            return ExitCode::from(EXIT_IO_ERROR);
+        }
+    }
+
+    match run(&args) {
+        Ok(code) => code,
+        Err(err) => {
+            error!("{:#}", err);
+            let caused_by = |is: fn(&(dyn std::error::Error + 'static)) -> bool| err.chain().any(is);
+            let io = |e: &(dyn std::error::Error + 'static)| {
+                e.is::<std::io::Error>() || matches!(e.downcast_ref::<DocsError>(), Some(DocsError::Io(_)))
+            };
+            if caused_by(io) {
+                ExitCode::from(EXIT_IO_ERROR)
+            } else if caused_by(|e| {
+                e.is::<serde_json::Error>()
+                    || e.is::<wasmparser::BinaryReaderError>()
+                    || matches!(
+                        e.downcast_ref::<DocsError>(),
+                        Some(
+                            DocsError::ComponentParse(_)
+                                | DocsError::ComponentTruncated
+                                | DocsError::NotUtf8(..)
+                                | DocsError::DigestMismatch { .. }
+                        )
+                    )
+            }) {
+                ExitCode::from(EXIT_DECODE_ERROR)
+            } else {
+                ExitCode::from(EXIT_OTHER_ERROR)
+            }
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<ExitCode> {
+    if args.component.is_dir() {
+        return run_directory(args);
+    }
+    let file_bytes = map_file(&args.component)
         .with_context(|| format!("Failed to read component file: {:?}", args.component))?;
+    if args.list_subcomponents {
+        return list_subcomponents(&file_bytes, args);
+    }
+    let wasm_bytes = match &args.subcomponent {
+        Some(spec) => find_subcomponent(&file_bytes, args, spec)?.bytes,
+        None => &file_bytes[..],
+    };
+
+    // The debugging views work on the undecoded bytes so that they remain
+    // usable for payloads this tool fails to parse.
+    if args.format == OutputFormat::All && args.out_dir.is_none() {
+        anyhow::bail!("--format all writes several files and needs --out-dir");
+    }
+    if args.format.is_site() && args.out_dir.is_none() {
+        anyhow::bail!("--format docusaurus and mkdocs write a page per world and need --out-dir");
+    }
+    if args.format == OutputFormat::Raw && args.out_dir.is_some() {
+        anyhow::bail!("--format raw is written to stdout; redirect it to a file instead of using --out-dir");
+    }
+    if !args.quiet && (args.hexdump || args.format == OutputFormat::Raw) {
+        let selected = select_section(wasm_bytes, args)
+            .with_context(|| format!("Failed to extract {} from component", args.section_name))?;
+        let Some(SelectedDocs { section, .. }) = selected else {
+            return report_no_docs(wasm_bytes, args);
+        };
+        if args.hexdump {
+            out!("{}", hexdump_section(&args.section_name, &section));
+        } else {
+            write_output(section.data).context("Failed to write section bytes")?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let Some(loaded) = load_docs(wasm_bytes, args)? else {
+        return report_no_docs(wasm_bytes, args);
+    };
+    if let Some(dir) = &args.out_dir
+        && !args.quiet
+    {
+        export(&loaded, args, dir)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(query) = &args.item {
+        return show_item(&loaded, args, query);
+    }
+    if !args.quiet {
+        let args = skeleton_args(&loaded, args);
+        display_docs(&loaded.docs, &loaded.ext, loaded.wit, &args, loaded.world.as_deref(), &loaded.signatures)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `--list-subcomponents`: the nested components, indented by depth, with
+/// the package each documents.
+fn list_subcomponents(wasm_bytes: &[u8], args: &Args) -> Result<ExitCode> {
+    let components = nested_components(wasm_bytes, &args.section_name)?;
+    if components.is_empty() {
+        anyhow::bail!("The component has no nested components");
+    }
+    if args.quiet {
+        return Ok(ExitCode::SUCCESS);
+    }
+    if args.format == OutputFormat::Json {
+        let listing: Vec<Value> = components
+            .iter()
+            .map(|c| {
+                json!({
+                    "index": c.index,
+                    "depth": c.depth,
+                    "name": c.name,
+                    "package": subcomponent_package(c),
+                    "has_docs": c.package_docs.is_some(),
+                })
+            })
+            .collect();
+        outln!("{}", serde_json::to_string_pretty(&listing)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    for c in &components {
+        let docs = match (subcomponent_package(c), &c.package_docs) {
+            (Some(package), _) => package,
+            (None, Some(_)) => "docs".to_string(),
+            (None, None) => "no docs".to_string(),
+        };
+        let indent = "  ".repeat(c.depth - 1);
+        outln!("{}{}: {} ({})", indent, c.index, c.name.unwrap_or("(unnamed)"), docs);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The nested component `spec` names: its index, its name, or the package
+/// it documents (`ns:pkg`, optionally with `@version`).
+fn find_subcomponent<'a>(wasm_bytes: &'a [u8], args: &Args, spec: &str) -> Result<NestedComponent<'a>> {
+    let components = nested_components(wasm_bytes, &args.section_name)?;
+    let count = components.len();
+    let mut matches: Vec<NestedComponent> = match spec.parse::<usize>() {
+        Ok(index) => components.into_iter().filter(|c| c.index == index).collect(),
+        Err(_) => components
+            .into_iter()
+            .filter(|c| {
+                c.name == Some(spec) || subcomponent_package(c).is_some_and(|p| same_versioned_name(&p, spec))
+            })
+            .collect(),
+    };
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => anyhow::bail!(
+            "No subcomponent `{}` among the component's {} nested component(s); see --list-subcomponents",
+            spec,
+            count
+        ),
+        _ => {
+            let indices: Vec<String> = matches.iter().map(|c| c.index.to_string()).collect();
+            anyhow::bail!("`{}` names several subcomponents ({}); give its index", spec, indices.join(", "))
+        }
+    }
+}
+
+/// The package a nested component documents, as recorded in its `package-docs-ext`.
+fn subcomponent_package(component: &NestedComponent) -> Option<String> {
+    let ext: Value = serde_json::from_slice(component.ext?.data).ok()?;
+    ext.get("package")?.as_str().map(String::from)
+}
+
+fn report_no_docs(wasm_bytes: &[u8], args: &Args) -> Result<ExitCode> {
+    error!("No {} found in component", args.section_name);
+    let nested = find_nested_docs(wasm_bytes)?.len();
+    if nested > 0 {
+        info!(
+            "{} nested component(s) carry package-docs; gather them with `wit-docs-inject --merge-nested`",
+            nested
+        );
+    }
+    Ok(ExitCode::from(EXIT_NO_DOCS))
+}
+
+/// `--item`: print the signature and docs of the item at `query`.
+fn show_item(loaded: &LoadedDocs, args: &Args, query: &str) -> Result<ExitCode> {
+    let package = loaded.ext.get("package").and_then(|p| p.as_str());
+    let items = collect_items(&loaded.docs, package, only_world(&loaded.docs, args).as_deref());
+    // An exact path wins over looser spellings of it.
+    let mut matches: Vec<&DocItem> = items.iter().filter(|item| item.path == query).collect();
+    if matches.is_empty() {
+        matches = items.iter().filter(|item| item_matches(&item.path, query)).collect();
+    }
+    let item = match matches.as_slice() {
+        [item] => *item,
+        [] => {
+            error!("No item `{}` in the docs", query);
+            return Ok(ExitCode::from(EXIT_NO_ITEM));
+        }
+        _ => {
+            let paths: Vec<&str> = matches.iter().map(|item| item.path.as_str()).collect();
+            anyhow::bail!("`{}` names several items ({}); give its full path", query, paths.join(", "));
+        }
+    };
+    if args.quiet {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let signature = loaded.signatures.get(args, &loaded.ext, loaded.world.as_deref()).get(&item.path);
+    match args.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut entry = json!({
+                "path": item.path,
+                "kind": item.kind,
+                "signature": signature,
+                "docs": item.docs,
+            });
+            if let Some(source) = source_of(&loaded.ext, args, &item.path) {
+                entry["source"] = Value::String(source.to_string());
+            }
+            let json = match args.format {
+                OutputFormat::Json => serde_json::to_string_pretty(&entry)?,
+                _ => serde_json::to_string(&entry)?,
+            };
+            outln!("{}", json);
+        }
+        _ => {
+            match &signature {
+                Some(signature) => outln!("{}: {}", item.path, signature),
+                None => outln!("{} ({})", item.path, item.kind),
+            }
+            if let Some(source) = source_of(&loaded.ext, args, &item.path) {
+                outln!("  at {}", source);
+            }
+            outln!();
+            match &item.docs {
+                Some(docs) => outln!("{}", render_docs(docs, args, 0, 0)),
+                None => outln!("(no documentation)"),
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Whether the item at `path` is the one `query` names: spelled the same up
+/// to [`normalize_name`], with a version on one side only matching any, and
+/// the package optional in `query` (`outgoing-handler#handle`).
+fn item_matches(path: &str, query: &str) -> bool {
+    let split = |p: &'_ str| match p.split_once('#') {
+        Some((container, member)) => (container.to_string(), Some(normalize_name(member))),
+        None => (p.to_string(), None),
+    };
+    let ((container, member), (wanted, wanted_member)) = (split(path), split(query));
+    if member != wanted_member {
+        return false;
+    }
+    same_versioned_name(&container, &wanted)
+        || (!wanted.contains(':') && container.rsplit('/').next().is_some_and(|name| same_versioned_name(name, &wanted)))
+}
+
+/// `--out-dir`: write the output of each format asked for to a file named
+/// after the component, decoding the docs once.
+fn export(loaded: &LoadedDocs, args: &Args, dir: &Path) -> Result<()> {
+    if args.format.is_site() {
+        return export_site(loaded, args, dir);
+    }
+    let formats = match &args.format {
+        OutputFormat::All => vec![OutputFormat::Json, OutputFormat::Markdown, OutputFormat::Html],
+        format => vec![format.clone()],
+    };
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let stem = args.component.file_stem().unwrap_or_default().to_string_lossy();
+    for format in formats {
+        let format_args = skeleton_args(loaded, &Args { format: format.clone(), ..args.clone() });
+        let output = capture(|| {
+            let world = loaded.world.as_deref();
+            display_docs(&loaded.docs, &loaded.ext, loaded.wit, &format_args, world, &loaded.signatures)
+        })?;
+        write_file(&dir.join(format!("{}.{}", stem, format.extension())), &output)?;
+    }
+    Ok(())
+}
+
+/// `--format docusaurus` and `mkdocs`: a folder of markdown pages for a
+/// docs-as-code site, named after the component: an overview of the package
+/// (`index.md`) and a page per world, with front matter, and the navigation
+/// listing them (`_category_.json` and `sidebar.json`, or `nav.yml`). Doc
+/// IDs and nav paths are relative to `dir`, which is meant to be the site's
+/// docs directory.
+fn export_site(loaded: &LoadedDocs, args: &Args, dir: &Path) -> Result<()> {
+    let stem = args.component.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let site = dir.join(&stem);
+    fs::create_dir_all(&site).with_context(|| format!("Failed to create {:?}", site))?;
+    let markdown_args = skeleton_args(loaded, &Args { format: OutputFormat::Markdown, ..args.clone() });
+    let links = load_link_map(args.link_map.as_deref())?;
+    let title = PackageInfo::new(&loaded.docs, &loaded.ext).name.unwrap_or(&stem).to_string();
+    let only_world = only_world(&loaded.docs, &markdown_args);
+    let worlds: Vec<&String> = loaded
+        .docs
+        .get("worlds")
+        .and_then(|w| w.as_object())
+        .into_iter()
+        .flat_map(|w| w.keys())
+        .filter(|name| only_world.as_ref().is_none_or(|w| w == *name))
+        .collect();
+
+    // (id, sidebar label, title, markdown)
+    let mut pages = Vec::new();
+    let overview = capture(|| {
+        display_markdown_header(&loaded.docs, &loaded.ext, &markdown_args, &links);
+        if !worlds.is_empty() {
+            outln!("## Worlds");
+            outln!();
+            for world in &worlds {
+                outln!("- [{}]({}.md)", world, world);
+            }
+        }
+        Ok(())
+    })?;
+    pages.push(("index".to_string(), "Overview".to_string(), title.clone(), overview));
+    for world in &worlds {
+        let world_args = Args { world: Some(world.to_string()), ..markdown_args.clone() };
+        let page = capture(|| {
+            let world = loaded.world.as_deref();
+            display_markdown_body(&loaded.docs, &loaded.ext, &world_args, world, &links, &loaded.signatures)
+        })?;
+        pages.push((world.to_string(), world.to_string(), format!("World: {}", world), page));
+    }
+
+    for (position, (id, label, page_title, markdown)) in pages.iter().enumerate() {
+        // JSON strings are valid YAML, and quote the `:` of package names.
+        let front_matter = match args.format {
+            OutputFormat::Docusaurus => format!(
+                "---\nid: {}\ntitle: {}\nsidebar_label: {}\nsidebar_position: {}\n---\n\n",
+                id,
+                serde_json::to_string(page_title)?,
+                serde_json::to_string(label)?,
+                position + 1
+            ),
+            _ => format!("---\ntitle: {}\n---\n\n", serde_json::to_string(page_title)?),
+        };
+        write_file(&site.join(format!("{}.md", id)), &[front_matter.as_bytes(), markdown.as_slice()].concat())?;
+    }
+
+    let doc_id = |id: &str| format!("{}/{}", stem, id);
+    match args.format {
+        OutputFormat::Docusaurus => {
+            let category = json!({ "label": title, "link": { "type": "doc", "id": doc_id("index") } });
+            write_file(&site.join("_category_.json"), serde_json::to_string_pretty(&category)?.as_bytes())?;
+            let sidebar = json!([{
+                "type": "category",
+                "label": title,
+                "link": { "type": "doc", "id": doc_id("index") },
+                "items": pages.iter().skip(1).map(|(id, ..)| doc_id(id)).collect::<Vec<_>>(),
+            }]);
+            write_file(&site.join("sidebar.json"), serde_json::to_string_pretty(&sidebar)?.as_bytes())?;
+        }
+        _ => {
+            let mut nav = format!("- {}:\n", serde_json::to_string(&title)?);
+            for (id, label, ..) in &pages {
+                nav.push_str(&format!("    - {}: {}.md\n", serde_json::to_string(label)?, doc_id(id)));
+            }
+            write_file(&site.join("nav.yml"), nav.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Write `bytes` to `path`, replacing it only once complete.
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = AtomicFile::create(path).with_context(|| format!("Failed to write {:?}", path))?;
+    file.file().write_all(bytes).with_context(|| format!("Failed to write {:?}", path))?;
+    file.commit().with_context(|| format!("Failed to replace {:?}", path))?;
+    info!("Wrote {:?}", path);
+    Ok(())
+}
+
+/// Run `f`, collecting what it prints instead of writing it to stdout.
+fn capture(f: impl FnOnce() -> Result<()>) -> Result<Vec<u8>> {
+    // Captures nest: the HTML output captures the markdown output.
+    let outer = CAPTURED.with_borrow_mut(|captured| captured.replace(Vec::new()));
+    let result = f();
+    let output = CAPTURED.with_borrow_mut(|captured| std::mem::replace(captured, outer)).unwrap_or_default();
+    result.map(|()| output)
+}
+
+fn capturing() -> bool {
+    CAPTURED.with_borrow(Option::is_some)
+}
+
+/// Write `bytes` to stdout, or to the buffer of a running [`capture`].
+fn write_output(bytes: &[u8]) -> std::io::Result<()> {
+    CAPTURED.with_borrow_mut(|captured| match captured {
+        Some(buffer) => {
+            buffer.extend_from_slice(bytes);
+            Ok(())
+        }
+        None => std::io::stdout().write_all(bytes),
+    })
+}
+
+/// Output for [`out!`] and [`outln!`], which fail like `print!` does.
+fn emit(text: &str) {
+    if let Err(err) = write_output(text.as_bytes()) {
+        panic!("failed printing to stdout: {}", err);
+    }
+}
+
+/// Decoded docs ready to display, with their `package-docs-ext` metadata
+/// (`Null` when absent), embedded WIT and the world selected by `--world`.
+struct LoadedDocs<'a> {
+    docs: Value,
+    ext: Value,
+    wit: Option<&'a [u8]>,
+    world: Option<String>,
+    /// Not docs but the component's API, from `--allow-missing`
+    skeleton: bool,
+    signatures: Signatures,
+}
+
+/// WIT signatures of the component's functions (see [`function_signatures`]),
+/// decoded the first time output needs them and then shared by every output
+/// written from the same docs.
+#[derive(Default)]
+struct Signatures(OnceCell<BTreeMap<String, String>>);
+
+impl Signatures {
+    fn get(&self, args: &Args, ext: &Value, world: Option<&str>) -> &BTreeMap<String, String> {
+        self.0.get_or_init(|| function_signatures(args, ext, world))
+    }
+}
+
+/// The docs of a component: from its docs section or, for a composed
+/// component, from the docs it gathered from its components. With
+/// `--allow-missing`, a component without either gets its API skeleton.
+fn load_docs<'a>(wasm_bytes: &'a [u8], args: &Args) -> Result<Option<LoadedDocs<'a>>> {
+    let mut loaded = load_section_docs(wasm_bytes, args)?;
+    if loaded.is_none() && args.allow_missing {
+        warn!("No {} found; showing the component's API without docs", args.section_name);
+        let (docs, ext) = api_skeleton(wasm_bytes).context("Failed to decode the component's WIT")?;
+        let world = select_world(&docs, &ext, args.world.as_deref())?;
+        loaded = Some(LoadedDocs { docs, ext, wit: None, world, skeleton: true, signatures: Signatures::default() });
+    }
+    if let Some(loaded) = &mut loaded {
+        filter_direction(&mut loaded.docs, &loaded.ext, args);
+    }
+    Ok(loaded)
+}
+
+/// `--imports-only` and `--exports-only`: drop the items of the other
+/// direction from the docs, so every format leaves them out: the worlds'
+/// functions, interfaces and (imported) types, and the package's interfaces
+/// no world uses in the direction shown.
+fn filter_direction(docs: &mut Value, ext: &Value, args: &Args) {
+    let (keep, other): (&[&str], &[&str]) = match (args.imports_only, args.exports_only) {
+        (true, _) => (&["funcs", "interfaces", "types"], &["func_exports", "interface_exports"]),
+        (_, true) => (&["func_exports", "interface_exports"], &["funcs", "interfaces", "types"]),
+        _ => return,
+    };
+    let mut used = BTreeSet::new();
+    let worlds = docs.get_mut("worlds").and_then(|w| w.as_object_mut());
+    for world in worlds.into_iter().flat_map(|w| w.values_mut()).filter_map(|w| w.as_object_mut()) {
+        for map in other {
+            world.remove(*map);
+        }
+        for map in keep {
+            used.extend(world.get(*map).and_then(|m| m.as_object()).into_iter().flat_map(|m| m.keys().cloned()));
+        }
+    }
+    let package = ext.get("package").and_then(|p| p.as_str());
+    if let Some(interfaces) = docs.get_mut("interfaces").and_then(|i| i.as_object_mut()) {
+        interfaces.retain(|name, _| used.contains(&qualify(package, name)));
+    }
+}
 
-    let docs_json = extract_package_docs(&wasm_bytes)
-        .with_context(|| "Failed to extract package-docs from component")?;
+/// The options to display `loaded` with: a skeleton lists every item,
+/// grouped by interface, unless the items were already asked for.
+fn skeleton_args(loaded: &LoadedDocs, args: &Args) -> Args {
+    let mut args = args.clone();
+    if loaded.skeleton && !lists_items(&args) && !args.functions_only && !args.worlds_only {
+        args.group_by = Some(GroupBy::Interface);
+    }
+    args
+}
+
+/// Docs-shaped JSON for a component's API, with no docs anywhere: the
+/// component's world and every interface it imports or exports, or all
+/// worlds and interfaces of a binary WIT package.
+fn api_skeleton(wasm_bytes: &[u8]) -> Result<(Value, Value)> {
+    let mut docs = json!({ "worlds": {}, "interfaces": {} });
+    let (resolve, worlds, ext) = match wit_component::decode(wasm_bytes)? {
+        DecodedWasm::WitPackage(resolve, pkg) => {
+            let worlds: Vec<WorldId> = resolve.packages[pkg].worlds.values().copied().collect();
+            for (name, &id) in &resolve.packages[pkg].interfaces {
+                docs["interfaces"][name] = interface_skeleton(&resolve, id);
+            }
+            let ext = json!({ "package": resolve.packages[pkg].name.to_string() });
+            (resolve, worlds, ext)
+        }
+        DecodedWasm::Component(resolve, world) => (resolve, vec![world], Value::Null),
+    };
+    for id in worlds {
+        let world = &resolve.worlds[id];
+        let mut world_docs = json!({});
+        for (imports, items) in [(true, &world.imports), (false, &world.exports)] {
+            for (key, item) in items {
+                let name = resolve.name_world_key(key);
+                match item {
+                    WorldItem::Function(_) => {
+                        world_docs[if imports { "funcs" } else { "func_exports" }][name] = json!({});
+                    }
+                    WorldItem::Interface { id, .. } => {
+                        let map = if imports { "interfaces" } else { "interface_exports" };
+                        match key {
+                            WorldKey::Name(_) => world_docs[map][name] = interface_skeleton(&resolve, *id),
+                            WorldKey::Interface(_) => {
+                                world_docs[map][&name] = json!({});
+                                // Package interfaces of a WIT package are
+                                // listed under their own name already.
+                                if ext.is_null() {
+                                    docs["interfaces"][name] = interface_skeleton(&resolve, *id);
+                                }
+                            }
+                        }
+                    }
+                    WorldItem::Type(ty) => world_docs["types"][name] = type_skeleton(&resolve, *ty),
+                }
+            }
+        }
+        docs["worlds"][&world.name] = world_docs;
+    }
+    Ok((docs, ext))
+}
+
+fn interface_skeleton(resolve: &Resolve, id: InterfaceId) -> Value {
+    let iface = &resolve.interfaces[id];
+    let mut docs = json!({ "funcs": {}, "types": {} });
+    for name in iface.functions.keys() {
+        docs["funcs"][name] = json!({});
+    }
+    for (name, &ty) in &iface.types {
+        docs["types"][name] = type_skeleton(resolve, ty);
+    }
+    docs
+}
+
+/// A type with its fields, cases or flags.
+fn type_skeleton(resolve: &Resolve, id: TypeId) -> Value {
+    let members: Vec<&str> = match &resolve.types[id].kind {
+        TypeDefKind::Record(record) => record.fields.iter().map(|f| f.name.as_str()).collect(),
+        TypeDefKind::Variant(variant) => variant.cases.iter().map(|c| c.name.as_str()).collect(),
+        TypeDefKind::Enum(e) => e.cases.iter().map(|c| c.name.as_str()).collect(),
+        TypeDefKind::Flags(flags) => flags.flags.iter().map(|f| f.name.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    if members.is_empty() {
+        return json!({});
+    }
+    let items: serde_json::Map<String, Value> = members.into_iter().map(|m| (m.to_string(), Value::Null)).collect();
+    json!({ "items": items })
+}
+
+fn load_section_docs<'a>(wasm_bytes: &'a [u8], args: &Args) -> Result<Option<LoadedDocs<'a>>> {
+    let selected = select_section(wasm_bytes, args)
+        .with_context(|| format!("Failed to extract {} from component", args.section_name))?;
+    let Some(SelectedDocs { section, ext, wit }) = selected else {
+        let Some(composed) = find_custom_section(wasm_bytes, COMPOSED_SECTION_NAME)? else {
+            return Ok(None);
+        };
+        let composed: Value = serde_json::from_slice(composed.data)
+            .context("Failed to parse package-docs-composed JSON")?;
+        let (mut docs, ext) = select_composed(&composed, args.world.as_deref())?;
+        apply_include_origins(&mut docs, &ext);
+        apply_world_interfaces(&mut docs, &ext);
+        let world = select_world(&docs, &ext, args.world.as_deref())?;
+        return Ok(Some(LoadedDocs { docs, ext, wit: None, world, skeleton: false, signatures: Signatures::default() }));
+    };
+
+    let ext = match ext {
+        Some(ext) => serde_json::from_slice(ext.data)
+            .context("Failed to parse package-docs-ext JSON")?,
+        None => wit_package_ext(wasm_bytes),
+    };
+    // Checked first, as a damaged payload often fails to decode too.
+    if verify_digest(section.data, &ext)? {
+        debug!("package-docs matches its recorded digest");
+    }
+    let Some(mut docs) = decode_package_docs(section.data)? else {
+        return Ok(None);
+    };
+    apply_include_origins(&mut docs, &ext);
+    apply_world_interfaces(&mut docs, &ext);
+    let world = select_world(&docs, &ext, args.world.as_deref())?;
+    let wit = wit.map(|wit| wit.data);
+    Ok(Some(LoadedDocs { docs, ext, wit, world, skeleton: false, signatures: Signatures::default() }))
+}
+
+/// For a binary WIT package (`wasm-tools component wit --wasm`, wkg), whose
+/// `package-docs` section is written by wit-component without a
+/// `package-docs-ext`: the package name the ext would record, so names are
+/// qualified as for injected components. `null` for anything else.
+fn wit_package_ext(wasm_bytes: &[u8]) -> Value {
+    match wit_component::decode(wasm_bytes) {
+        Ok(DecodedWasm::WitPackage(resolve, pkg)) => {
+            let package = resolve.packages[pkg].name.to_string();
+            debug!(%package, "binary WIT package");
+            json!({ "package": package })
+        }
+        Ok(DecodedWasm::Component(..)) => Value::Null,
+        Err(err) => {
+            debug!("no package name: {:#}", err);
+            Value::Null
+        }
+    }
+}
+
+/// View every component below a directory: each one's docs under its path
+/// relative to the directory, or, as JSON, one object keyed by those paths.
+/// Components without docs are skipped.
+fn run_directory(args: &Args) -> Result<ExitCode> {
+    if args.item.is_some() || args.out_dir.is_some() || args.subcomponent.is_some() || args.list_subcomponents {
+        anyhow::bail!("--item, --out-dir and --subcomponent need a single component, not a directory");
+    }
+    if args.hexdump
+        || !matches!(
+            args.format,
+            OutputFormat::Pretty
+                | OutputFormat::Markdown
+                | OutputFormat::Json
+                | OutputFormat::SearchIndex
+                | OutputFormat::Ndjson
+        )
+    {
+        anyhow::bail!("A directory can only be viewed with --format pretty, markdown, json, search-index or ndjson");
+    }
+    let files = wasm_files(&args.component)
+        .with_context(|| format!("Failed to list components in {:?}", args.component))?;
+
+    let mut combined = serde_json::Map::new();
+    let mut index = Vec::new();
+    for file in &files {
+        let name = file
+            .strip_prefix(&args.component)
+            .unwrap_or(file)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        // Signatures and WIT are read from the component being shown.
+        let file_args = Args { component: file.clone(), ..args.clone() };
+        let loaded = map_file(file)
+            .with_context(|| format!("Failed to read component file: {:?}", file))
+            .and_then(|bytes| {
+                let loaded = load_docs(&bytes, &file_args)?;
+                Ok(loaded.map(|l| (skeleton_args(&l, &file_args), l.docs, l.ext, l.world)))
+            });
+        let (file_args, docs, ext, world) = match loaded {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => {
+                info!("{}: no {}", name, args.section_name);
+                continue;
+            }
+            Err(err) => {
+                warn!("{}: skipped: {:#}", name, err);
+                continue;
+            }
+        };
+        if args.quiet {
+            combined.insert(name, Value::Null);
+            continue;
+        }
+        match args.format {
+            OutputFormat::Json => {
+                combined.insert(name, docs_json(&docs, &ext, &file_args));
+            }
+            OutputFormat::SearchIndex => {
+                index.extend(search_index(&docs, &ext, &file_args).into_iter().map(|mut entry| {
+                    entry["component"] = Value::String(name.clone());
+                    entry
+                }));
+                combined.insert(name, Value::Null);
+            }
+            OutputFormat::Ndjson => {
+                write_ndjson(&docs, &ext, &file_args, world.as_deref(), Some(&name), &Signatures::default())?;
+                combined.insert(name, Value::Null);
+            }
+            OutputFormat::Markdown => {
+                if !combined.is_empty() {
+                    outln!("---");
+                    outln!();
+                }
+                outln!("# Component: `{}`", name);
+                outln!();
+                display_markdown(&docs, &ext, &file_args, world.as_deref(), &Signatures::default())?;
+                combined.insert(name, Value::Null);
+            }
+            _ => {
+                if !combined.is_empty() {
+                    outln!();
+                }
+                outln!("🧩 Component: {}", name);
+                outln!("{}", "═".repeat(60));
+                display_pretty(&docs, &ext, &file_args)?;
+                combined.insert(name, Value::Null);
+            }
+        }
+    }
+
+    if combined.is_empty() {
+        error!("No {} found in {} component(s) in {:?}", args.section_name, files.len(), args.component);
+        return Ok(ExitCode::from(EXIT_NO_DOCS));
+    }
+    info!("{} of {} component(s) carry docs", combined.len(), files.len());
+    if args.quiet {
+        return Ok(ExitCode::SUCCESS);
+    }
+    match args.format {
+        OutputFormat::Json => outln!("{}", serde_json::to_string_pretty(&json!({ "components": combined }))?),
+        OutputFormat::SearchIndex => outln!("{}", serde_json::to_string_pretty(&index)?),
+        _ => {}
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    if let Some(docs) = docs_json {
-        display_docs(&docs, &args)?;
+/// A docs section to show, with the companion sections written alongside it.
+struct SelectedDocs<'a> {
+    section: DocsSection<'a>,
+    ext: Option<DocsSection<'a>>,
+    wit: Option<DocsSection<'a>>,
+}
+
+/// The docs section to show, when there is one. Of several (docs for more
+/// than one package, or a component injected twice), `--section-index` or
+/// `--package` picks one; otherwise the one defining `--world`, or the last
+/// if they all document the same package, since injecting appends.
+fn select_section<'a>(wasm_bytes: &'a [u8], args: &Args) -> Result<Option<SelectedDocs<'a>>> {
+    let sections = find_custom_sections(wasm_bytes, &args.section_name)?;
+    if sections.is_empty() {
+        return Ok(None);
+    }
+    let exts = find_custom_sections(wasm_bytes, EXT_SECTION_NAME)?;
+    let wits = find_custom_sections(wasm_bytes, WIT_SECTION_NAME)?;
+    // `wit-docs-inject` writes the companions right after their docs section.
+    let companion = |list: &[DocsSection<'a>], index: usize| match sections.len() {
+        1 => list.first().copied(),
+        _ => {
+            let start = sections[index].offset;
+            let end = sections.get(index + 1).map_or(usize::MAX, |s| s.offset);
+            list.iter().find(|s| s.offset > start && s.offset < end).copied()
+        }
+    };
+    let package_of = |index: usize| -> Option<String> {
+        let ext: Value = serde_json::from_slice(companion(&exts, index)?.data).ok()?;
+        ext.get("package")?.as_str().map(String::from)
+    };
+    let listing = || {
+        (0..sections.len())
+            .map(|i| format!("{}: {}", i, package_of(i).as_deref().unwrap_or("unknown package")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let index = if let Some(index) = args.section_index {
+        if index >= sections.len() {
+            anyhow::bail!(
+                "--section-index {} is out of range; the component has {} {} section(s) ({})",
+                index,
+                sections.len(),
+                args.section_name,
+                listing()
+            );
+        }
+        index
+    } else if let Some(package) = &args.package {
+        let matches = |p: &str| same_versioned_name(p, package);
+        match (0..sections.len()).rev().find(|&i| package_of(i).is_some_and(|p| matches(&p))) {
+            Some(index) => index,
+            None => anyhow::bail!("No {} section documents package `{}` ({})", args.section_name, package, listing()),
+        }
+    } else if sections.len() == 1 {
+        0
+    } else {
+        let packages: Vec<Option<String>> = (0..sections.len()).map(package_of).collect();
+        let defines_world = |index: usize| {
+            let Some(spec) = args.world.as_deref().map(WorldSpec::parse) else {
+                return false;
+            };
+            let docs = decode_package_docs(sections[index].data).ok().flatten();
+            packages[index].as_deref().is_none_or(|p| spec.matches_package(p))
+                && docs.is_some_and(|docs| docs["worlds"].as_object().and_then(|w| find_key(w, &spec.name)).is_some())
+        };
+        let with_world: Vec<usize> = (0..sections.len()).filter(|&i| defines_world(i)).collect();
+        if let [index] = with_world.as_slice() {
+            *index
+        } else if packages.iter().all(|p| *p == packages[0]) {
+            info!(
+                "{} {} sections document the same package; showing the last one (use --section-index for another)",
+                sections.len(),
+                args.section_name
+            );
+            sections.len() - 1
+        } else {
+            anyhow::bail!(
+                "Component has {} {} sections ({}); choose one with --section-index, --package or --world",
+                sections.len(),
+                args.section_name,
+                listing()
+            );
+        }
+    };
+    debug!(index, sections = sections.len(), "selected docs section");
+    Ok(Some(SelectedDocs {
+        section: sections[index],
+        ext: companion(&exts, index),
+        wit: companion(&wits, index),
+    }))
+}
+
+/// The docs and ext of the package to show from a `package-docs-composed`
+/// section: the one `--world` belongs to, or the only one.
+fn select_composed(composed: &Value, spec: Option<&str>) -> Result<(Value, Value)> {
+    let packages = composed.get("packages").and_then(|p| p.as_object());
+    let known = || packages.into_iter().flatten().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+    let candidates: Vec<&Value> = match spec {
+        Some(spec) => {
+            let world = WorldSpec::parse(spec);
+            packages
+                .into_iter()
+                .flatten()
+                .filter(|(package, entry)| {
+                    world.matches_package(package)
+                        && entry["docs"]["worlds"].get(&world.name).is_some()
+                })
+                .map(|(_, entry)| entry)
+                .collect()
+        }
+        None => packages.into_iter().flatten().map(|(_, entry)| entry).collect(),
+    };
+    match (candidates.as_slice(), spec) {
+        ([entry], _) => Ok((entry["docs"].clone(), entry["ext"].clone())),
+        ([], Some(spec)) => anyhow::bail!("No component documents world `{}` (packages: {})", spec, known()),
+        ([], None) => anyhow::bail!("No package-docs found in composed component"),
+        (_, Some(spec)) => anyhow::bail!(
+            "Several components document world `{}` ({}); qualify it with its package",
+            spec,
+            known()
+        ),
+        (_, None) => anyhow::bail!(
+            "Composed component documents several packages ({}); choose a world with --world",
+            known()
+        ),
+    }
+}
+
+/// The name of the world selected with `--world`, if any, spelled as in
+/// `docs`.
+fn only_world(docs: &Value, args: &Args) -> Option<String> {
+    let name = WorldSpec::parse(args.world.as_deref()?).name;
+    match docs.get("worlds").and_then(|w| w.as_object()).and_then(|w| find_key(w, &name)) {
+        Some((key, _)) => Some(key.clone()),
+        None => Some(name),
+    }
+}
+
+/// Merge the include origins recorded by `wit-docs-inject` into `docs`.
+///
+/// Each included item gains an `included_from` field naming its source world.
+/// Included interface imports from dependency packages have no entry in
+/// `package-docs`, so one is added from the docs carried in the extension.
+fn apply_include_origins(docs: &mut Value, ext: &Value) {
+    let Some(includes) = ext.get("includes").and_then(|i| i.as_object()) else {
+        return;
+    };
+    for (world_name, directions) in includes {
+        let world = &mut docs["worlds"][world_name];
+        for (direction, items) in directions.as_object().into_iter().flatten() {
+            // Exports live in their own maps unless no import shares the name.
+            let (maps, fallback): (&[&str], _) = if direction == "exports" {
+                (&["func_exports", "interface_exports", "funcs", "interfaces"], "interface_exports")
+            } else {
+                (&["funcs", "interfaces", "types"], "interfaces")
+            };
+            for (item_name, origin) in items.as_object().into_iter().flatten() {
+                let from = origin["from"].clone();
+                let existing = maps.iter().find_map(|map| {
+                    world.get(*map).and_then(|m| m.get(item_name))?;
+                    Some(*map)
+                });
+                match existing {
+                    Some(map) => {
+                        if let Some(item) = world[map][item_name].as_object_mut() {
+                            item.insert("included_from".to_string(), from);
+                        }
+                    }
+                    None => {
+                        if let Some(item_docs) = origin.get("docs") {
+                            world[fallback][item_name] = serde_json::json!({
+                                "docs": item_docs,
+                                "included_from": from,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Add the interfaces each world imports or exports by name, as recorded by
+/// `wit-docs-inject`, to the world's `interfaces` and `interface_exports`.
+/// Worlds without docs of their own are added for them.
+fn apply_world_interfaces(docs: &mut Value, ext: &Value) {
+    let Some(worlds) = ext.get("world_interfaces").and_then(|w| w.as_object()) else {
+        return;
+    };
+    for (world_name, directions) in worlds {
+        let world = &mut docs["worlds"][world_name];
+        for (direction, map) in [("imports", "interfaces"), ("exports", "interface_exports")] {
+            for (name, item) in directions.get(direction).and_then(|i| i.as_object()).into_iter().flatten() {
+                if world.get(map).and_then(|m| m.get(name)).is_none() {
+                    world[map][name] = item.clone();
+                }
+            }
+        }
+    }
+}
+
+fn included_from(item: &Value) -> Option<&str> {
+    item.get("included_from").and_then(|f| f.as_str())
+}
+
+fn hexdump_section(name: &str, section: &DocsSection) -> String {
+    let mut out = format!(
+        "{}: {} bytes of section data at offset {:#x}\n",
+        name,
+        section.data.len(),
+        section.offset
+    );
+    let Some((version, payload)) = section.data.split_first() else {
+        out.push_str("(empty section)\n");
+        return out;
+    };
+
+    out.push_str("-- version byte\n");
+    out.push_str(&format!("{:08x}  {:02x}{:47}|{}|\n", section.offset, version, "", printable(*version)));
+    out.push_str(&format!("-- JSON payload ({} bytes)\n", payload.len()));
+    let payload_offset = section.offset + 1;
+    for (i, chunk) in payload.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk.iter().map(|b| printable(*b)).collect();
+        out.push_str(&format!("{:08x}  {:<47}  |{}|\n", payload_offset + i * 16, hex.join(" "), ascii));
+    }
+    out.push_str(&format!("-- end of section at offset {:#x}\n", payload_offset + payload.len()));
+    out
+}
+
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
     } else {
-        eprintln!("No package-docs found in component");
-        std::process::exit(1);
+        '.'
+    }
+}
+
+/// The docs as `--format json` prints them: the world selected by `--world`,
+/// or all of them with the package name and version.
+fn docs_json(docs: &Value, ext: &Value, args: &Args) -> Value {
+    match only_world(docs, args) {
+        Some(name) => docs["worlds"][&name].clone(),
+        None => {
+            let package = PackageInfo::new(docs, ext);
+            let mut docs = docs.clone();
+            if let (Some(name), Some(object)) = (package.name, docs.as_object_mut()) {
+                let mut entry = json!({ "name": name, "version": package.version });
+                for (key, value) in ext.get("metadata").and_then(|m| m.as_object()).into_iter().flatten() {
+                    entry[key] = value.clone();
+                }
+                object.insert("package".to_string(), entry);
+            }
+            docs
+        }
+    }
+}
+
+fn display_docs(
+    docs: &Value,
+    ext: &Value,
+    wit: Option<&[u8]>,
+    args: &Args,
+    world: Option<&str>,
+    signatures: &Signatures,
+) -> Result<()> {
+    match args.format {
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(&docs_json(docs, ext, args))?);
+        }
+        OutputFormat::Pretty => {
+            display_pretty(docs, ext, args)?;
+        }
+        OutputFormat::Markdown => {
+            display_markdown(docs, ext, args, world, signatures)?;
+        }
+        OutputFormat::Wit => {
+            match wit {
+                // Embedded with `wit-docs-inject --embed-wit`: print it as is.
+                Some(wit) if !colors(args) => write_output(wit).context("Failed to write embedded WIT")?,
+                Some(wit) => out!("{}", highlight_wit(&String::from_utf8_lossy(wit))),
+                None => {
+                    // The decoded WIT belongs to the component, not to the
+                    // documented package, so the header stays a plain comment.
+                    let mut text = String::new();
+                    let package = PackageInfo::new(docs, ext);
+                    if let Some(name) = package.name {
+                        match package.version {
+                            Some(version) => text.push_str(&format!("// Docs from package {} (version {})\n", name, version)),
+                            None => text.push_str(&format!("// Docs from package {}\n", name)),
+                        }
+                        for (label, value) in &package.metadata {
+                            text.push_str(&format!("// {}: {}\n", label, value));
+                        }
+                        for (key, value) in &package.custom {
+                            text.push_str(&format!("// {}: {}\n", key, value));
+                        }
+                        for line in package.docs.unwrap_or_default().lines() {
+                            text.push_str(&format!("// {}\n", line));
+                        }
+                        text.push('\n');
+                    }
+                    text.push_str(&wit_with_docs(docs, ext, args, world)?);
+                    text.push('\n');
+                    if colors(args) {
+                        text = highlight_wit(&text);
+                    }
+                    out!("{}", text);
+                }
+            }
+        }
+        OutputFormat::Ts => {
+            display_typescript(docs, ext, args)?;
+        }
+        OutputFormat::Tree => {
+            display_tree(docs, ext, args);
+        }
+        OutputFormat::SearchIndex => {
+            display_search_index(docs, ext, args)?;
+        }
+        OutputFormat::Ndjson => {
+            write_ndjson(docs, ext, args, world, None, signatures)?;
+        }
+        OutputFormat::Html => {
+            display_html(docs, ext, args, world, signatures)?;
+        }
+        OutputFormat::Raw => unreachable!("raw output is written before decoding"),
+        OutputFormat::All => unreachable!("--format all is split into its formats"),
+        OutputFormat::Docusaurus | OutputFormat::Mkdocs => unreachable!("sites are written page by page"),
+    }
+    Ok(())
+}
+
+/// Type declarations mirroring the `package-docs` JSON model (plus the
+/// `included_from` origins merged in from `package-docs-ext`).
+const TS_DOCS_TYPES: &str = "\
+export type Stability = Record<string, unknown>;
+
+export interface ItemDocs {
+  docs?: string;
+  stability?: Stability;
+  /** World an `include`d item came from */
+  included_from?: string;
+}
+
+export interface TypeDocs extends ItemDocs {
+  /** Docs of record fields, variant/enum cases and flags; `null` for members
+   * listed without docs (`--allow-missing`) */
+  items?: Record<string, string | null>;
+}
+
+/** Older payloads may store a function's docs as a bare string. */
+export type FunctionDocs = ItemDocs | string;
+
+export interface InterfaceDocs extends ItemDocs {
+  funcs?: Record<string, FunctionDocs>;
+  types?: Record<string, TypeDocs>;
+}
+
+export interface WorldDocs extends ItemDocs {
+  /** Imported interfaces (and exports, when no import has the same name) */
+  interfaces?: Record<string, InterfaceDocs>;
+  interface_exports?: Record<string, InterfaceDocs>;
+  types?: Record<string, TypeDocs>;
+  /** Imported functions (and exports, when no import has the same name) */
+  funcs?: Record<string, FunctionDocs>;
+  func_exports?: Record<string, FunctionDocs>;
+  interface_import_stability?: Record<string, Stability>;
+  interface_export_stability?: Record<string, Stability>;
+}
+
+export interface PackageDocs {
+  docs?: string;
+  worlds?: Record<string, WorldDocs>;
+  interfaces?: Record<string, InterfaceDocs>;
+}
+";
+
+fn display_typescript(docs: &Value, ext: &Value, args: &Args) -> Result<()> {
+    let only_world = only_world(docs, args);
+    let mut docs = docs.clone();
+    if let Some(name) = only_world
+        && let Some(worlds) = docs.get_mut("worlds").and_then(|w| w.as_object_mut())
+    {
+        worlds.retain(|world, _| *world == name);
     }
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let source = args.component.file_name().unwrap_or_default().to_string_lossy();
 
+    outln!("// Generated by wit-docs-view from {}; do not edit.", source);
+    outln!();
+    out!("{}", TS_DOCS_TYPES);
+    outln!();
+    outln!("/** Name of the documented WIT package, when recorded. */");
+    outln!("export const packageName: string | null = {};", serde_json::to_string(&package)?);
+    outln!();
+    outln!("/** Version of the documented WIT package, when recorded. */");
+    outln!(
+        "export const packageVersion: string | null = {};",
+        serde_json::to_string(&PackageInfo::new(&docs, ext).version)?
+    );
+    outln!();
+    outln!("export const docs = {} as const satisfies PackageDocs;", serde_json::to_string_pretty(&docs)?);
+    outln!();
+    outln!("export default docs;");
     Ok(())
 }
 
-fn extract_package_docs(wasm_bytes: &[u8]) -> Result<Option<Value>> {
-    let parser = WasmParser::new(0);
-    
-    for payload in parser.parse_all(wasm_bytes) {
-        let payload = payload.context("Failed to parse WebAssembly")?;
-        
-        if let Payload::CustomSection(reader) = payload {
-            if reader.name() == "package-docs" {
-                let data = reader.data();
-                // Skip the first byte (version) and parse the JSON
-                if data.len() > 1 {
-                    let json_data = &data[1..];
-                    let docs: Value = serde_json::from_slice(json_data)
-                        .context("Failed to parse package-docs JSON")?;
-                    return Ok(Some(docs));
+/// The package, its worlds and interfaces, and their items as a tree, each
+/// line with the summary of the item's docs. The `--*-only` flags keep the
+/// items they select and the ones they belong to.
+fn display_tree(docs: &Value, ext: &Value, args: &Args) {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let items = collect_items(docs, package, only_world(docs, args).as_deref());
+    let index: HashMap<&str, usize> = items.iter().enumerate().map(|(i, item)| (item.path.as_str(), i)).collect();
+    let parents: Vec<Option<usize>> = items.iter().map(|item| tree_parent(item, &index)).collect();
+
+    let mut shown = vec![false; items.len()];
+    for (i, item) in items.iter().enumerate() {
+        let mut next = shows_kind(args, item).then_some(i);
+        while let Some(i) = next.filter(|&i| !shown[i]) {
+            shown[i] = true;
+            next = parents[i];
+        }
+    }
+
+    let info = PackageInfo::new(docs, ext);
+    if let Some(package) = package {
+        let summary = info.docs.map(|d| format!(" — {}", summary(d))).unwrap_or_default();
+        outln!("📦 {}{}", package, summary);
+    }
+    print_tree(&items, &parents, &shown, None, "");
+}
+
+/// The item `item` is listed under in the tree: a type for type members, a
+/// resource for its functions, else the item's world or interface.
+fn tree_parent(item: &DocItem, index: &HashMap<&str, usize>) -> Option<usize> {
+    let container = item.container.as_deref()?;
+    let name = item.path.get(container.len() + 1..).unwrap_or_default();
+    let separator = &item.path[container.len()..container.len() + 1];
+    let parent = match item.kind {
+        "member" => item.path.rsplit_once('.').map(|(ty, _)| ty.to_string()),
+        "function" => resource_of(name).map(|resource| format!("{}{}{}", container, separator, resource)),
+        _ => None,
+    };
+    parent.and_then(|p| index.get(p.as_str()).copied()).or_else(|| index.get(container).copied())
+}
+
+fn print_tree(items: &[DocItem], parents: &[Option<usize>], shown: &[bool], parent: Option<usize>, prefix: &str) {
+    let children: Vec<usize> = (0..items.len()).filter(|&i| shown[i] && parents[i] == parent).collect();
+    for (n, &i) in children.iter().enumerate() {
+        let item = &items[i];
+        let last = n + 1 == children.len();
+        // Names are shown relative to the item above, or else to the
+        // item's world or interface.
+        let relative = |base: &str| item.path.strip_prefix(base).and_then(|rest| rest.get(1..)).filter(|r| !r.is_empty());
+        let name = parent
+            .and_then(|p| relative(&items[p].path))
+            .or_else(|| item.container.as_deref().and_then(relative))
+            .unwrap_or(&item.path);
+        let summary = item.docs.as_deref().map(|d| format!(" — {}", summary(d))).unwrap_or_default();
+        outln!("{}{}{} {}{}", prefix, if last { "└── " } else { "├── " }, item.kind, name, summary);
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        print_tree(items, parents, shown, Some(i), &prefix);
+    }
+}
+
+/// The markdown output rendered as a standalone HTML page.
+fn display_html(docs: &Value, ext: &Value, args: &Args, world: Option<&str>, signatures: &Signatures) -> Result<()> {
+    let markdown = capture(|| display_markdown(docs, ext, args, world, signatures))?;
+    let markdown = String::from_utf8_lossy(&markdown);
+    let mut body = String::new();
+    let options = pulldown_cmark::Options::ENABLE_TABLES | pulldown_cmark::Options::ENABLE_STRIKETHROUGH;
+    let events = pulldown_cmark::Parser::new_ext(&markdown, options).map(sanitize_event);
+    pulldown_cmark::html::push_html(&mut body, events);
+
+    let title = PackageInfo::new(docs, ext).name.unwrap_or("Component docs");
+    outln!("<!DOCTYPE html>");
+    outln!("<html>");
+    outln!("<head>");
+    outln!("<meta charset=\"utf-8\">");
+    outln!("<title>{}</title>", html_escape(title));
+    outln!("</head>");
+    outln!("<body>");
+    out!("{}", body);
+    outln!("</body>");
+    outln!("</html>");
+    Ok(())
+}
+
+/// Docs come from third-party components, and the page may be served from a
+/// docs portal: HTML in them is shown as text, and links and images lead
+/// nowhere unless [`is_safe_url`]. Only the markup this viewer writes itself,
+/// anchors and links to them, is kept.
+fn sanitize_event(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Html(html) | Event::InlineHtml(html) if !is_own_html(&html) => Event::Text(html),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Link { link_type, dest_url: "".into(), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Image { link_type, dest_url: "".into(), title, id })
+        }
+        event => event,
+    }
+}
+
+/// Whether `html` is one of the tags written by [`anchor_tag`] and
+/// [`linked_signature`].
+fn is_own_html(html: &str) -> bool {
+    let is_anchor = |id: &str| id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    let anchor_in = |prefix: &str| {
+        html.strip_prefix(prefix).and_then(|rest| rest.strip_suffix("\">")).is_some_and(is_anchor)
+    };
+    matches!(html, "</a>" | "<code>" | "</code>") || anchor_in("<a id=\"") || anchor_in("<a href=\"#")
+}
+
+/// Whether docs may link to `url`: an `http`, `https` or `mailto` URL, or a
+/// relative one (`#anchor`, `orders.html`). Other schemes, `javascript:` and
+/// `data:` among them, can run script or forge content.
+fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in the scheme.
+    let url: String = url.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect();
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn display_search_index(docs: &Value, ext: &Value, args: &Args) -> Result<()> {
+    outln!("{}", serde_json::to_string_pretty(&search_index(docs, ext, args))?);
+    Ok(())
+}
+
+fn search_index(docs: &Value, ext: &Value, args: &Args) -> Vec<Value> {
+    let package = PackageInfo::new(docs, ext);
+    let package_entry = match (package.name, kind_filtered(args)) {
+        (Some(_), false) => Some(json!({
+            "path": ext["package"],
+            "kind": "package",
+            "summary": package.docs.map(summary),
+        })),
+        _ => None,
+    };
+    let items = organized_items(docs, ext, args).into_iter().map(|(_, item)| {
+        let mut entry = json!({
+            "path": item.path,
+            "kind": item.kind,
+            "summary": item.docs.as_deref().map(summary),
+        });
+        if let Some(source) = source_of(ext, args, &item.path) {
+            entry["source"] = Value::String(source.to_string());
+        }
+        entry
+    });
+    package_entry.into_iter().chain(items).collect()
+}
+
+/// Write one JSON object per line: the package, then each item with its
+/// signature (functions only) and full docs. Lines are written as they are
+/// produced, so consumers can start before the end. With `component`, each
+/// object names the component it comes from.
+fn write_ndjson(
+    docs: &Value,
+    ext: &Value,
+    args: &Args,
+    world: Option<&str>,
+    component: Option<&str>,
+    signatures: &Signatures,
+) -> Result<()> {
+    let signatures = signatures.get(args, ext, world);
+    let package = PackageInfo::new(docs, ext);
+    let package_entry = match (package.name, kind_filtered(args)) {
+        (Some(_), false) => Some(json!({
+            "path": ext["package"],
+            "kind": "package",
+            "signature": null,
+            "docs": package.docs,
+        })),
+        _ => None,
+    };
+    let items = organized_items(docs, ext, args).into_iter().map(|(_, item)| {
+        let mut entry = json!({
+            "path": item.path,
+            "kind": item.kind,
+            "signature": signatures.get(&item.path),
+            "docs": item.docs,
+        });
+        if let Some(source) = source_of(ext, args, &item.path) {
+            entry["source"] = Value::String(source.to_string());
+        }
+        entry
+    });
+
+    for mut entry in package_entry.into_iter().chain(items) {
+        if let Some(component) = component {
+            entry["component"] = Value::String(component.to_string());
+        }
+        match write_output(format!("{}\n", serde_json::to_string(&entry)?).as_bytes()) {
+            // The consumer (`head`, ...) has seen enough.
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+            written => written.context("Failed to write ndjson output")?,
+        }
+    }
+    Ok(())
+}
+
+/// The items selected by `--world` and the kind filters, in the order asked
+/// for with `--group-by` and `--sort`, each with the label of its group
+/// (empty without `--group-by`).
+fn organized_items(docs: &Value, ext: &Value, args: &Args) -> Vec<(String, DocItem)> {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let mut items: Vec<DocItem> = collect_items(docs, package, only_world(docs, args).as_deref())
+        .into_iter()
+        .filter(|item| shows_kind(args, item))
+        .collect();
+
+    match args.sort {
+        Some(SortOrder::Name) => items.sort_by(|a, b| a.path.cmp(&b.path)),
+        Some(SortOrder::SourceOrder) => {
+            // Items without a recorded location keep their order, at the end.
+            let location = |item: &DocItem| {
+                let source = ext.get("sources")?.get(&item.path)?.as_str()?;
+                let (file, line) = source.rsplit_once(':')?;
+                Some((file.to_string(), line.parse::<usize>().ok()?))
+            };
+            items.sort_by_cached_key(|item| location(item).map_or((1, None), |l| (0, Some(l))));
+        }
+        None => {}
+    }
+
+    // Tables list functions per interface unless grouped otherwise, and so
+    // do HTML pages.
+    let by_interface = args.format == OutputFormat::Html || (args.table && args.format == OutputFormat::Markdown);
+    let group_by = args.group_by.or(by_interface.then_some(GroupBy::Interface));
+    let Some(group_by) = group_by else {
+        return items.into_iter().map(|item| (String::new(), item)).collect();
+    };
+    let mut grouped: Vec<(usize, String, DocItem)> = Vec::new();
+    let mut containers: Vec<String> = Vec::new();
+    for item in items {
+        let (rank, label) = match group_by {
+            GroupBy::Interface => {
+                let container = item.container.clone().unwrap_or_else(|| item.path.clone());
+                let rank = containers.iter().position(|c| *c == container).unwrap_or_else(|| {
+                    containers.push(container.clone());
+                    containers.len() - 1
+                });
+                (rank, container)
+            }
+            GroupBy::Direction => match item.direction {
+                Some("export") => (0, "Exports".to_string()),
+                Some(_) => (1, "Imports".to_string()),
+                None => (2, "Definitions".to_string()),
+            },
+            GroupBy::Kind => {
+                let kinds = [
+                    ("world", "Worlds"),
+                    ("interface", "Interfaces"),
+                    ("function", "Functions"),
+                    ("resource", "Resources"),
+                    ("type", "Types"),
+                    ("member", "Type members"),
+                ];
+                let rank = kinds.iter().position(|(kind, _)| *kind == item.kind).unwrap_or(kinds.len());
+                (rank, kinds.get(rank).map_or("Other", |(_, label)| label).to_string())
+            }
+        };
+        grouped.push((rank, label, item));
+    }
+    // Stable, so the `--sort` order holds within each group.
+    grouped.sort_by_key(|(rank, _, _)| *rank);
+    grouped.into_iter().map(|(_, label, item)| (label, item)).collect()
+}
+
+/// The documented package, as shown in the header of each output format.
+struct PackageInfo<'a> {
+    /// `ns:pkg`, when recorded at injection time
+    name: Option<&'a str>,
+    version: Option<&'a str>,
+    docs: Option<&'a str>,
+    /// Authors, license, home page and repository, as given to
+    /// `wit-docs-inject`: label and value
+    metadata: Vec<(&'static str, String)>,
+    /// Pairs given with `wit-docs-inject --meta`
+    custom: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> PackageInfo<'a> {
+    fn new(docs: &'a Value, ext: &'a Value) -> Self {
+        let package = ext.get("package").and_then(|p| p.as_str());
+        let (name, version) = match package.map(|p| p.split_once('@')) {
+            Some(Some((name, version))) => (Some(name), Some(version)),
+            Some(None) => (package, None),
+            None => (None, None),
+        };
+        let docs = docs.get("docs").and_then(|d| d.as_str()).filter(|d| !d.trim().is_empty());
+        let fields = &ext["metadata"];
+        let mut metadata = Vec::new();
+        if let Some(authors) = fields.get("authors").and_then(|a| a.as_array()).filter(|a| !a.is_empty()) {
+            let authors: Vec<&str> = authors.iter().filter_map(|a| a.as_str()).collect();
+            metadata.push(("Authors", authors.join(", ")));
+        }
+        for (key, label) in [("license", "License"), ("homepage", "Homepage"), ("repository", "Repository")] {
+            if let Some(value) = fields.get(key).and_then(|v| v.as_str()) {
+                metadata.push((label, value.to_string()));
+            }
+        }
+        let custom = ext["metadata"]["custom"].as_object().into_iter().flatten();
+        let custom = custom.filter_map(|(key, value)| Some((key.as_str(), value.as_str()?))).collect();
+        PackageInfo { name, version, docs, metadata, custom }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_none() && self.docs.is_none() && self.metadata.is_empty() && self.custom.is_empty()
+    }
+}
+
+/// The icon of a package metadata field in pretty output.
+fn metadata_icon(label: &str) -> &'static str {
+    match label {
+        "Authors" => "👤",
+        "License" => "📜",
+        "Homepage" => "🏠",
+        _ => "🔗",
+    }
+}
+
+/// Whether any `--*-only` flag narrows the output to some kinds of items,
+/// which leaves no room for the package header.
+fn kind_filtered(args: &Args) -> bool {
+    args.functions_only || args.worlds_only || args.types_only || args.interfaces_only || args.resources_only
+}
+
+/// Whether the `--*-only` flags select `item`. They combine: each adds its
+/// kind of item, and without any every item is shown.
+fn shows_kind(args: &Args, item: &DocItem) -> bool {
+    let filtered = kind_filtered(args);
+    let resource_function = item.path.find('[').and_then(|i| resource_of(&item.path[i..])).is_some();
+    !filtered
+        || (args.functions_only && item.kind == "function")
+        || (args.worlds_only && item.kind == "world")
+        || (args.types_only && matches!(item.kind, "type" | "resource" | "member"))
+        || (args.interfaces_only && item.kind == "interface")
+        || (args.resources_only && (item.kind == "resource" || resource_function))
+}
+
+/// Whether pretty and markdown output list items one by one instead of
+/// walking each world's functions, which is needed to show anything else.
+/// HTML pages always do, so that the types named in signatures are on the
+/// page to link to.
+fn lists_items(args: &Args) -> bool {
+    args.group_by.is_some()
+        || args.sort.is_some()
+        || args.types_only
+        || args.interfaces_only
+        || args.resources_only
+        || args.format == OutputFormat::Html
+        || (args.table && args.format == OutputFormat::Markdown)
+}
+
+/// The `file:line` recorded for the item at `path`, with `--show-source`.
+fn source_of<'a>(ext: &'a Value, args: &Args, path: &str) -> Option<&'a str> {
+    if !args.show_source {
+        return None;
+    }
+    ext.get("sources")?.get(path)?.as_str()
+}
+
+/// Whether doc comments are rendered as markdown: not with `--plain`, nor
+/// without colors (see `--color`).
+fn renders_markdown(args: &Args) -> bool {
+    !args.plain && colors(args)
+}
+
+/// A doc comment for the terminal, its markdown rendered unless
+/// [`renders_markdown`] says otherwise, and shortened to its first sentence
+/// with `--summary`. Text starts at column `start`; wrapped and rendered
+/// lines after the first are indented by `indent` to line up under the item
+/// they describe.
+fn render_docs(docs: &str, args: &Args, start: usize, indent: usize) -> String {
+    let docs = if args.summary { first_sentence(docs) } else { docs.to_string() };
+    let width = wrap_width(args);
+    if !renders_markdown(args) {
+        return match width {
+            Some(width) => wrap(&docs, width, start, indent).join(&format!("\n{}", " ".repeat(indent))),
+            None => docs,
+        };
+    }
+    // termimad wraps every line to the same width: that of the first.
+    let width = width.map(|width| width.saturating_sub(start).max(MIN_WRAP_WIDTH));
+    let rendered = termimad::MadSkin::default().text(&docs, width).to_string();
+    rendered.trim_end().lines().collect::<Vec<_>>().join(&format!("\n{}", " ".repeat(indent)))
+}
+
+/// A one-line [`summary`], rendered and wrapped like [`render_docs`].
+fn render_summary(summary: &str, args: &Args, start: usize, indent: usize) -> String {
+    let lines = match wrap_width(args) {
+        Some(width) => wrap(summary, width, start, indent),
+        None => vec![summary.to_string()],
+    };
+    let skin = termimad::MadSkin::default();
+    let lines: Vec<String> = match renders_markdown(args) {
+        true => lines.iter().map(|line| skin.inline(line).to_string()).collect(),
+        false => lines,
+    };
+    lines.join(&format!("\n{}", " ".repeat(indent)))
+}
+
+/// Narrowest column count doc text is wrapped to, however far right it
+/// starts.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// The columns to wrap doc comments to: `--width`, or the terminal's when
+/// printing to one. `None` leaves lines as written.
+fn wrap_width(args: &Args) -> Option<usize> {
+    match args.width {
+        Some(0) => None,
+        Some(width) => Some(width),
+        None if !capturing() && std::io::stdout().is_terminal() => Some(termimad::terminal_size().0 as usize),
+        None => None,
+    }
+}
+
+/// `text` word-wrapped to `width` columns, as lines without their
+/// indentation: the first starts at column `start`, the others at `indent`.
+/// Fenced code blocks and indented lines are kept as they are.
+fn wrap(text: &str, width: usize, start: usize, indent: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut column = start;
+    let mut in_fence = false;
+    let mut break_line = |line: &mut String, column: &mut usize| {
+        lines.push(std::mem::take(line));
+        *column = indent;
+    };
+    for (n, source) in text.lines().enumerate() {
+        if n > 0 {
+            break_line(&mut line, &mut column);
+        }
+        let fence = source.trim_start().starts_with("```");
+        if in_fence || fence || source.starts_with([' ', '\t']) {
+            in_fence ^= fence;
+            line.push_str(source);
+            continue;
+        }
+        for (i, word) in source.split_whitespace().enumerate() {
+            let len = word.chars().count();
+            let space = usize::from(i > 0);
+            // Words that don't fit start a new line, unless nothing precedes
+            // them on this one.
+            if column > indent && column + space + len > width.max(indent + MIN_WRAP_WIDTH) {
+                break_line(&mut line, &mut column);
+            } else if space > 0 {
+                line.push(' ');
+                column += 1;
+            }
+            line.push_str(word);
+            column += len;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// First paragraph of a doc comment, on one line.
+fn summary(docs: &str) -> String {
+    docs.trim()
+        .split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The first sentence of a doc comment, on one line, followed by `…` when
+/// the comment goes on.
+fn first_sentence(docs: &str) -> String {
+    let paragraph = summary(docs);
+    let end = paragraph
+        .char_indices()
+        .find(|&(i, c)| matches!(c, '.' | '!' | '?') && paragraph[i + 1..].starts_with(' '))
+        .map(|(i, _)| i + 1);
+    match end {
+        Some(end) => format!("{} …", &paragraph[..end]),
+        None if docs.trim().contains("\n\n") => format!("{} …", paragraph),
+        None => paragraph,
+    }
+}
+
+fn display_pretty(docs: &Value, ext: &Value, args: &Args) -> Result<()> {
+    let package = PackageInfo::new(docs, ext);
+    if !package.is_empty() && !kind_filtered(args) {
+        match (package.name, package.version) {
+            (Some(name), Some(version)) => outln!("📦 Package: {} (version {})", name, version),
+            (Some(name), None) => outln!("📦 Package: {}", name),
+            (None, _) => outln!("📦 Package"),
+        }
+        for (label, value) in &package.metadata {
+            outln!("   {} {}: {}", metadata_icon(label), label, value);
+        }
+        for (key, value) in &package.custom {
+            outln!("   🔖 {}: {}", key, value);
+        }
+        if let Some(docs) = package.docs {
+            outln!("   📝 {}", render_docs(docs, args, 6, 6));
+        }
+        outln!();
+    }
+
+    if lists_items(args) {
+        return display_pretty_items(docs, ext, args);
+    }
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let only_world = only_world(docs, args);
+    // `--functions-only` and `--worlds-only` add up rather than cancel out.
+    let show_worlds = !args.functions_only || args.worlds_only;
+    let show_functions = !args.worlds_only || args.functions_only;
+    if let Some(worlds) = docs.get("worlds").and_then(|w| w.as_object()) {
+        for (world_name, world_data) in worlds {
+            if only_world.as_ref().is_some_and(|w| w != world_name) {
+                continue;
+            }
+            let world_path = qualify(package, world_name);
+            if show_worlds {
+                outln!("🌍 World: {}", world_name);
+                
+                if let Some(world_docs) = world_data.get("docs").and_then(|d| d.as_str()) {
+                    outln!("   📝 {}", render_docs(world_docs, args, 6, 6));
+                } else {
+                    outln!("   📝 (no documentation)");
+                }
+                if let Some(source) = source_of(ext, args, &world_path) {
+                    outln!("   📍 {}", source);
+                }
+                outln!();
+            }
+
+            if show_functions {
+                if let Some(func_exports) = world_data.get("func_exports").and_then(|f| f.as_object())
+                    && !func_exports.is_empty()
+                {
+                    if show_worlds {
+                        outln!("📤 Exported Functions:");
+                    }
+                    
+                    for (func_name, func_data) in func_exports {
+                        out!("   🔧 {}", func_name);
+                        
+                        if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
+                            out!(": {}", render_docs(func_docs, args, item_column(func_name), 6));
+                        } else {
+                            out!(": (no documentation)");
+                        }
+                        if let Some(origin) = included_from(func_data) {
+                            out!(" (from {})", origin);
+                        }
+                        match source_of(ext, args, &format!("{}#{}", world_path, func_name)) {
+                            Some(source) => outln!(" [{}]", source),
+                            None => outln!(),
+                        }
+                    }
+                    outln!();
+                }
+
+                if let Some(func_imports) = world_data.get("funcs").and_then(|f| f.as_object())
+                    && !func_imports.is_empty()
+                {
+                    if show_worlds {
+                        outln!("📥 Imported Functions:");
+                    }
+                    
+                    for (func_name, func_data) in func_imports {
+                        out!("   🔧 {}", func_name);
+                        
+                        if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
+                            out!(": {}", render_docs(func_docs, args, item_column(func_name), 6));
+                        } else {
+                            out!(": (no documentation)");
+                        }
+                        if let Some(origin) = included_from(func_data) {
+                            out!(" (from {})", origin);
+                        }
+                        match source_of(ext, args, &format!("{}#{}", world_path, func_name)) {
+                            Some(source) => outln!(" [{}]", source),
+                            None => outln!(),
+                        }
+                    }
+                    outln!();
+                }
+            }
+
+            if show_worlds {
+                for (map, heading) in [("interface_exports", "📤 Exported Interfaces:"), ("interfaces", "📥 Imported Interfaces:")] {
+                    let Some(interfaces) = world_data.get(map).and_then(|i| i.as_object()).filter(|i| !i.is_empty()) else {
+                        continue;
+                    };
+                    outln!("{}", heading);
+                    for (iface_name, iface_data) in interfaces {
+                        out!("   🔌 {}", iface_name);
+                        match iface_data.get("docs").and_then(|d| d.as_str()) {
+                            Some(iface_docs) => out!(": {}", render_docs(iface_docs, args, item_column(iface_name), 6)),
+                            None => out!(": (no documentation)"),
+                        }
+                        if let Some(origin) = included_from(iface_data) {
+                            out!(" (from {})", origin);
+                        }
+                        match source_of(ext, args, &format!("{}#{}", world_path, iface_name)) {
+                            Some(source) => outln!(" [{}]", source),
+                            None => outln!(),
+                        }
+                    }
+                    outln!();
                 }
             }
         }
+    } else {
+        outln!("No world documentation found");
     }
     
-    Ok(None)
+    Ok(())
+}
+
+fn display_markdown(docs: &Value, ext: &Value, args: &Args, world: Option<&str>, signatures: &Signatures) -> Result<()> {
+    let links = load_link_map(args.link_map.as_deref())?;
+    display_markdown_header(docs, ext, args, &links);
+    display_markdown_body(docs, ext, args, world, &links, signatures)
 }
 
-fn display_docs(docs: &Value, args: &Args) -> Result<()> {
-    match args.format {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(docs)?);
+/// The package header of markdown output: name, version, metadata and docs.
+fn display_markdown_header(docs: &Value, ext: &Value, args: &Args, links: &LinkMap) {
+    let package = PackageInfo::new(docs, ext);
+    if !package.is_empty() && !kind_filtered(args) {
+        match package.name {
+            Some(name) => outln!("# Package: {}", name),
+            None => outln!("# Package"),
         }
-        OutputFormat::Pretty => {
-            display_pretty(docs, args)?;
+        outln!();
+        if let Some(version) = package.version {
+            outln!("*Version {}*", version);
+            outln!();
         }
-        OutputFormat::Markdown => {
-            display_markdown(docs, args)?;
+        if !package.metadata.is_empty() || !package.custom.is_empty() {
+            for (label, value) in &package.metadata {
+                match *label {
+                    "Homepage" | "Repository" => outln!("- **{}:** <{}>", label, value),
+                    _ => outln!("- **{}:** {}", label, value),
+                }
+            }
+            for (key, value) in &package.custom {
+                outln!("- **{}:** {}", key, value);
+            }
+            outln!();
         }
-        OutputFormat::Wit => {
-            display_wit_with_docs(docs, args)?;
+        if let Some(docs) = package.docs {
+            outln!("{}", link_references(docs, links));
+            outln!();
         }
     }
-    Ok(())
 }
 
-fn display_pretty(docs: &Value, args: &Args) -> Result<()> {
+/// Markdown output after the package header: the worlds with their
+/// functions and interfaces, or the items listed one by one.
+fn display_markdown_body(
+    docs: &Value,
+    ext: &Value,
+    args: &Args,
+    world: Option<&str>,
+    links: &LinkMap,
+    signatures: &Signatures,
+) -> Result<()> {
+    if lists_items(args) {
+        return display_markdown_items(docs, ext, args, world, links, signatures);
+    }
+    display_contents(&world_contents(docs, ext, args));
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let only_world = only_world(docs, args);
+    // `--functions-only` and `--worlds-only` add up rather than cancel out.
+    let show_worlds = !args.functions_only || args.worlds_only;
+    let show_functions = !args.worlds_only || args.functions_only;
     if let Some(worlds) = docs.get("worlds").and_then(|w| w.as_object()) {
         for (world_name, world_data) in worlds {
-            if !args.functions_only {
-                println!("🌍 World: {}", world_name);
+            if only_world.as_ref().is_some_and(|w| w != world_name) {
+                continue;
+            }
+            let world_path = qualify(package, world_name);
+            if show_worlds {
+                outln!("# {}World: {}", anchor_tag(&world_path), world_name);
+                outln!();
                 
                 if let Some(world_docs) = world_data.get("docs").and_then(|d| d.as_str()) {
-                    println!("   📝 {}", world_docs);
+                    outln!("{}", link_references(world_docs, links));
                 } else {
-                    println!("   📝 (no documentation)");
+                    outln!("*(no documentation)*");
+                }
+                outln!();
+                if let Some(source) = source_of(ext, args, &world_path) {
+                    outln!("*Source: `{}`*", source);
+                    outln!();
                 }
-                println!();
             }
 
-            if !args.worlds_only {
-                if let Some(func_exports) = world_data.get("func_exports").and_then(|f| f.as_object()) {
-                    if !func_exports.is_empty() {
-                        if !args.functions_only {
-                            println!("📤 Exported Functions:");
+            if show_functions {
+                if let Some(func_exports) = world_data.get("func_exports").and_then(|f| f.as_object())
+                    && !func_exports.is_empty()
+                {
+                    if show_worlds {
+                        outln!("## Exported Functions");
+                        outln!();
+                    }
+                    
+                    for (func_name, func_data) in func_exports {
+                        outln!("### {}`{}`", anchor_tag(&format!("{}#{}", world_path, func_name)), func_name);
+                        if let Some(origin) = included_from(func_data) {
+                            outln!("*Included from `{}`*", origin);
+                            outln!();
+                        }
+                        if let Some(source) = source_of(ext, args, &format!("{}#{}", world_path, func_name)) {
+                            outln!("*Source: `{}`*", source);
+                            outln!();
                         }
                         
-                        for (func_name, func_data) in func_exports {
-                            print!("   🔧 {}", func_name);
-                            
-                            if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
-                                println!(": {}", func_docs);
-                            } else {
-                                println!(": (no documentation)");
-                            }
+                        if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
+                            outln!("{}", link_references(func_docs, links));
+                        } else {
+                            outln!("*(no documentation)*");
                         }
-                        println!();
+                        outln!();
                     }
                 }
 
-                if let Some(func_imports) = world_data.get("func_imports").and_then(|f| f.as_object()) {
-                    if !func_imports.is_empty() {
-                        if !args.functions_only {
-                            println!("📥 Imported Functions:");
+                if let Some(func_imports) = world_data.get("funcs").and_then(|f| f.as_object())
+                    && !func_imports.is_empty()
+                {
+                    if show_worlds {
+                        outln!("## Imported Functions");
+                        outln!();
+                    }
+                    
+                    for (func_name, func_data) in func_imports {
+                        outln!("### {}`{}`", anchor_tag(&format!("{}#{}", world_path, func_name)), func_name);
+                        if let Some(origin) = included_from(func_data) {
+                            outln!("*Included from `{}`*", origin);
+                            outln!();
+                        }
+                        if let Some(source) = source_of(ext, args, &format!("{}#{}", world_path, func_name)) {
+                            outln!("*Source: `{}`*", source);
+                            outln!();
                         }
                         
-                        for (func_name, func_data) in func_imports {
-                            print!("   🔧 {}", func_name);
-                            
-                            if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
-                                println!(": {}", func_docs);
-                            } else {
-                                println!(": (no documentation)");
-                            }
+                        if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
+                            outln!("{}", link_references(func_docs, links));
+                        } else {
+                            outln!("*(no documentation)*");
+                        }
+                        outln!();
+                    }
+                }
+            }
+
+            if show_worlds {
+                for (map, heading) in [("interface_exports", "## Exported Interfaces"), ("interfaces", "## Imported Interfaces")] {
+                    let Some(interfaces) = world_data.get(map).and_then(|i| i.as_object()).filter(|i| !i.is_empty()) else {
+                        continue;
+                    };
+                    outln!("{}", heading);
+                    outln!();
+                    for (iface_name, iface_data) in interfaces {
+                        let path = format!("{}#{}", world_path, iface_name);
+                        outln!("### {}`{}`", anchor_tag(&path), iface_name);
+                        if let Some(origin) = included_from(iface_data) {
+                            outln!("*Included from `{}`*", origin);
+                            outln!();
                         }
-                        println!();
+                        if let Some(source) = source_of(ext, args, &path) {
+                            outln!("*Source: `{}`*", source);
+                            outln!();
+                        }
+                        match iface_data.get("docs").and_then(|d| d.as_str()) {
+                            Some(iface_docs) => outln!("{}", link_references(iface_docs, links)),
+                            None => outln!("*(no documentation)*"),
+                        }
+                        outln!();
                     }
                 }
             }
         }
     } else {
-        println!("No world documentation found");
+        outln!("No world documentation found");
     }
     
     Ok(())
 }
 
-fn display_markdown(docs: &Value, args: &Args) -> Result<()> {
-    if let Some(worlds) = docs.get("worlds").and_then(|w| w.as_object()) {
-        for (world_name, world_data) in worlds {
-            if !args.functions_only {
-                println!("# World: {}", world_name);
-                println!();
-                
-                if let Some(world_docs) = world_data.get("docs").and_then(|d| d.as_str()) {
-                    println!("{}", world_docs);
-                } else {
-                    println!("*(no documentation)*");
-                }
-                println!();
+/// The column the docs of an item start at in pretty output, after
+/// `   🔧 name: ` (icons take two columns).
+fn item_column(name: &str) -> usize {
+    6 + name.chars().count() + 2
+}
+
+/// Pretty output as a flat list of items, for `--group-by` and `--sort`.
+fn display_pretty_items(docs: &Value, ext: &Value, args: &Args) -> Result<()> {
+    let mut group = None;
+    for (label, item) in organized_items(docs, ext, args) {
+        if group.as_ref() != Some(&label) {
+            if group.is_some() {
+                outln!();
             }
+            if !label.is_empty() {
+                outln!("📂 {}", label);
+            }
+            group = Some(label);
+        }
+        let icon = match item.kind {
+            "world" => "🌍",
+            "interface" => "🔌",
+            "function" => "🔧",
+            "resource" => "📦",
+            _ => "🏷️",
+        };
+        let docs = item
+            .docs
+            .as_deref()
+            .map(|d| if args.summary { first_sentence(d) } else { summary(d) })
+            .map_or_else(|| "(no documentation)".to_string(), |d| {
+                render_summary(&d, args, item_column(&item.path), 6)
+            });
+        out!("   {} {}: {}", icon, item.path, docs);
+        match source_of(ext, args, &item.path) {
+            Some(source) => outln!(" [{}]", source),
+            None => outln!(),
+        }
+    }
+    Ok(())
+}
 
-            if !args.worlds_only {
-                if let Some(func_exports) = world_data.get("func_exports").and_then(|f| f.as_object()) {
-                    if !func_exports.is_empty() {
-                        if !args.functions_only {
-                            println!("## Exported Functions");
-                            println!();
-                        }
-                        
-                        for (func_name, func_data) in func_exports {
-                            println!("### `{}`", func_name);
-                            
-                            if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
-                                println!("{}", func_docs);
-                            } else {
-                                println!("*(no documentation)*");
-                            }
-                            println!();
-                        }
-                    }
+/// Markdown output as a flat list of items, for `--group-by`, `--sort` and
+/// `--table`.
+fn display_markdown_items(
+    docs: &Value,
+    ext: &Value,
+    args: &Args,
+    world: Option<&str>,
+    links: &LinkMap,
+    signatures: &Signatures,
+) -> Result<()> {
+    let mut groups: Vec<(String, Vec<DocItem>)> = Vec::new();
+    for (label, item) in organized_items(docs, ext, args) {
+        match groups.last_mut() {
+            Some((last, items)) if *last == label => items.push(item),
+            _ => groups.push((label, vec![item])),
+        }
+    }
+    display_contents(&item_contents(&groups, args));
+    // Signatures are shown in tables, and in HTML under each function too.
+    let no_signatures = BTreeMap::new();
+    let signatures = match args.table || args.format == OutputFormat::Html {
+        true => signatures.get(args, ext, world),
+        false => &no_signatures,
+    };
+    // In HTML, the types named in signatures link to their docs on the page.
+    let types: Vec<TypeTarget> = match args.format {
+        OutputFormat::Html => groups
+            .iter()
+            .flat_map(|(_, items)| items)
+            .filter(|item| item.kind == "type" || item.kind == "resource")
+            .filter_map(|item| {
+                let (_, name) = item.path.rsplit_once('#')?;
+                let (name, container, path) = (normalize_name(name), item.container.clone(), item.path.clone());
+                Some(TypeTarget { name, container, path })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for (label, items) in groups {
+        if !label.is_empty() {
+            outln!("## {}", label);
+            outln!();
+        }
+        // The table takes the place of the group's first function.
+        let in_table = |item: &DocItem| args.table && item.kind == "function";
+        let functions: Vec<&DocItem> = items.iter().filter(|item| in_table(item)).collect();
+        let mut table_shown = false;
+        for item in &items {
+            if in_table(item) {
+                if !table_shown {
+                    display_function_table(&functions, signatures, &types, ext, args);
+                    table_shown = true;
                 }
+                continue;
+            }
+            outln!("### {}`{}`", anchor_tag(&item.path), item.path);
+            outln!();
+            outln!("*{}*", item.kind);
+            outln!();
+            if args.format == OutputFormat::Html
+                && let Some(signature) = signatures.get(&item.path)
+            {
+                outln!("<code>{}</code>", linked_signature(signature, item.container.as_deref(), &types));
+                outln!();
+            }
+            if let Some(source) = source_of(ext, args, &item.path) {
+                outln!("*Source: `{}`*", source);
+                outln!();
+            }
+            match &item.docs {
+                Some(docs) => outln!("{}", link_references(docs, links)),
+                None => outln!("*(no documentation)*"),
+            }
+            outln!();
+        }
+    }
+    Ok(())
+}
 
-                if let Some(func_imports) = world_data.get("func_imports").and_then(|f| f.as_object()) {
-                    if !func_imports.is_empty() {
-                        if !args.functions_only {
-                            println!("## Imported Functions");
-                            println!();
-                        }
-                        
-                        for (func_name, func_data) in func_imports {
-                            println!("### `{}`", func_name);
-                            
-                            if let Some(func_docs) = func_data.get("docs").and_then(|d| d.as_str()) {
-                                println!("{}", func_docs);
-                            } else {
-                                println!("*(no documentation)*");
-                            }
-                            println!();
-                        }
+/// A markdown table of `functions`, named relative to their interface or world.
+fn display_function_table(
+    functions: &[&DocItem],
+    signatures: &BTreeMap<String, String>,
+    types: &[TypeTarget],
+    ext: &Value,
+    args: &Args,
+) {
+    if args.show_source {
+        outln!("| Function | Signature | Summary | Source |");
+        outln!("| --- | --- | --- | --- |");
+    } else {
+        outln!("| Function | Signature | Summary |");
+        outln!("| --- | --- | --- |");
+    }
+    for item in functions {
+        let name = match &item.container {
+            Some(container) => item.path.get(container.len() + 1..).unwrap_or(&item.path),
+            None => &item.path,
+        };
+        let signature = match signatures.get(&item.path) {
+            Some(signature) if args.format == OutputFormat::Html => {
+                format!("<code>{}</code>", linked_signature(signature, item.container.as_deref(), types))
+            }
+            Some(signature) => format!("`{}`", signature),
+            None => String::new(),
+        };
+        let summary = item.docs.as_deref().map_or_else(String::new, summary).replace('|', "\\|");
+        out!("| {}`{}` | {} | {} |", anchor_tag(&item.path), name, signature, summary);
+        match source_of(ext, args, &item.path) {
+            Some(source) => outln!(" `{}` |", source),
+            None if args.show_source => outln!("  |"),
+            None => outln!(),
+        }
+    }
+    outln!();
+}
+
+/// A type documented on the page, that signatures can link to.
+struct TypeTarget {
+    /// As [`normalize_name`] spells it
+    name: String,
+    container: Option<String>,
+    path: String,
+}
+
+/// `signature` as HTML, with every type it names linked to the type's docs:
+/// the type of that name in the function's own interface or world, else the
+/// only one of that name on the page, which may come from a dependency.
+fn linked_signature(signature: &str, container: Option<&str>, types: &[TypeTarget]) -> String {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '%';
+    let mut html = String::new();
+    let mut rest = signature;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric() || c == '%') {
+        html.push_str(&html_escape(&rest[..start]));
+        let end = rest[start..].find(|c: char| !is_name(c)).map_or(rest.len(), |len| start + len);
+        let word = &rest[start..end];
+        rest = &rest[end..];
+        // Parameter names are followed by a colon.
+        let target = match rest.starts_with(':') {
+            true => None,
+            false => type_target(word, container, types),
+        };
+        match target {
+            Some(path) => html.push_str(&format!("<a href=\"#{}\">{}</a>", anchor(path), html_escape(word))),
+            None => html.push_str(&html_escape(word)),
+        }
+    }
+    html.push_str(&html_escape(rest));
+    html
+}
+
+fn type_target<'a>(word: &str, container: Option<&str>, types: &'a [TypeTarget]) -> Option<&'a str> {
+    let name = normalize_name(word);
+    let named: Vec<&'a TypeTarget> = types.iter().filter(|t| t.name == name).collect();
+    let own = named.iter().copied().find(|t| container.is_some() && t.container.as_deref() == container);
+    match (own, named.as_slice()) {
+        (Some(own), _) => Some(own.path.as_str()),
+        (None, &[only]) => Some(only.path.as_str()),
+        _ => None,
+    }
+}
+
+/// WIT signatures of the component's functions, keyed by item path. They come
+/// from the embedded WIT when there is one, and otherwise from the
+/// component's type information, whose world takes the name of `world`.
+/// Signatures that can't be recovered are left out.
+fn function_signatures(args: &Args, ext: &Value, world: Option<&str>) -> BTreeMap<String, String> {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let wit = map_file(&args.component).map_err(anyhow::Error::from).and_then(|bytes| {
+        let bytes = match &args.subcomponent {
+            Some(spec) => find_subcomponent(&bytes, args, spec)?.bytes,
+            None => &bytes[..],
+        };
+        Ok(component_wit(bytes)?)
+    });
+    match wit {
+        Ok((resolve, component_world)) => signatures_of(&resolve, component_world, package, world),
+        Err(err) => {
+            debug!("no function signatures: {:#}", err);
+            BTreeMap::new()
+        }
+    }
+}
+
+fn signatures_of(
+    resolve: &Resolve,
+    component_world: Option<wit_parser::WorldId>,
+    package: Option<&str>,
+    world: Option<&str>,
+) -> BTreeMap<String, String> {
+    // Paths are only qualified when the docs' are.
+    let qualifier = |pkg: Option<wit_parser::PackageId>| {
+        package.and(pkg).map(|pkg| resolve.packages[pkg].name.to_string())
+    };
+    let mut signatures = BTreeMap::new();
+    for (_, iface) in resolve.interfaces.iter() {
+        let Some(name) = &iface.name else {
+            continue;
+        };
+        let path = qualify(qualifier(iface.package).as_deref(), name);
+        for (func_name, func) in &iface.functions {
+            signatures.insert(format!("{}#{}", path, func_name), function_signature(resolve, func));
+        }
+    }
+    for (id, w) in resolve.worlds.iter() {
+        let path = match component_world {
+            // The decoded world is anonymous and belongs to no documented package.
+            Some(component_world) if component_world == id => match world {
+                Some(world) => qualify(package, world),
+                None => continue,
+            },
+            Some(_) => continue,
+            None => qualify(qualifier(w.package).as_deref(), &w.name),
+        };
+        for (key, item) in w.imports.iter().chain(w.exports.iter()) {
+            let WorldKey::Name(name) = key else {
+                continue;
+            };
+            match item {
+                WorldItem::Function(func) => {
+                    signatures.insert(format!("{}#{}", path, name), function_signature(resolve, func));
+                }
+                WorldItem::Interface { id, .. } if resolve.interfaces[*id].name.is_none() => {
+                    for (func_name, func) in &resolve.interfaces[*id].functions {
+                        signatures.insert(format!("{}#{}.{}", path, name, func_name), function_signature(resolve, func));
                     }
                 }
+                _ => {}
             }
         }
-    } else {
-        println!("No world documentation found");
     }
-    
-    Ok(())
+    signatures
+}
+
+/// An entry of the table of contents: nesting level, title and anchor
+/// (`None` for titles that only group the entries below them).
+type ContentsEntry = (usize, String, Option<String>);
+
+/// Contents of the default markdown output: worlds and their functions.
+fn world_contents(docs: &Value, ext: &Value, args: &Args) -> Vec<ContentsEntry> {
+    let package = ext.get("package").and_then(|p| p.as_str());
+    let only_world = only_world(docs, args);
+    let show_worlds = !args.functions_only || args.worlds_only;
+    let show_functions = !args.worlds_only || args.functions_only;
+    let mut entries = Vec::new();
+    for (world_name, world_data) in docs.get("worlds").and_then(|w| w.as_object()).into_iter().flatten() {
+        if only_world.as_ref().is_some_and(|w| w != world_name) {
+            continue;
+        }
+        let world_path = qualify(package, world_name);
+        if show_worlds {
+            entries.push((0, format!("World: {}", world_name), Some(anchor(&world_path))));
+        }
+        let maps = match (show_functions, show_worlds) {
+            (true, true) => &["func_exports", "funcs", "interface_exports", "interfaces"][..],
+            (true, false) => &["func_exports", "funcs"][..],
+            _ => &["interface_exports", "interfaces"][..],
+        };
+        for map in maps {
+            for name in world_data.get(*map).and_then(|f| f.as_object()).into_iter().flatten().map(|(k, _)| k) {
+                let path = format!("{}#{}", world_path, name);
+                entries.push((show_worlds as usize, format!("`{}`", name), Some(anchor(&path))));
+            }
+        }
+    }
+    entries
+}
+
+/// Contents of markdown output listing items: the groups and their items.
+fn item_contents(groups: &[(String, Vec<DocItem>)], args: &Args) -> Vec<ContentsEntry> {
+    let mut entries = Vec::new();
+    for (label, items) in groups {
+        if !label.is_empty() {
+            entries.push((0, label.clone(), None));
+        }
+        let level = !label.is_empty() as usize;
+        for item in items {
+            let title = match &item.container {
+                // Table rows are named relative to their interface or world.
+                Some(container) if args.table && item.kind == "function" => {
+                    format!("`{}`", item.path.get(container.len() + 1..).unwrap_or(&item.path))
+                }
+                _ => format!("`{}`", item.path),
+            };
+            entries.push((level, title, Some(anchor(&item.path))));
+        }
+    }
+    entries
+}
+
+/// A linked table of contents; nothing for fewer than two entries.
+fn display_contents(entries: &[ContentsEntry]) {
+    if entries.len() < 2 {
+        return;
+    }
+    outln!("## Contents");
+    outln!();
+    for (level, title, anchor) in entries {
+        let indent = "  ".repeat(*level);
+        match anchor {
+            Some(anchor) => outln!("{}- [{}](#{})", indent, title, anchor),
+            None => outln!("{}- {}", indent, title),
+        }
+    }
+    outln!();
+}
+
+/// The anchor ID of the item at `path`, derived from the path alone so that
+/// links stay valid when the docs are regenerated: `acme:shop/orders@1.2.0#place`
+/// => `acme-shop-orders-1-2-0-place`.
+fn anchor(path: &str) -> String {
+    let mut id = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// An empty HTML anchor for `path`, placed inside a heading or table cell (on
+/// a line of its own it would start an HTML block).
+fn anchor_tag(path: &str) -> String {
+    format!("<a id=\"{}\"></a>", anchor(path))
+}
+
+fn load_link_map(path: Option<&Path>) -> Result<LinkMap> {
+    // The WASI proposal repositories host the published docs for each package
+    let mut links = LinkMap::from([(
+        "wasi".to_string(),
+        "https://github.com/WebAssembly/wasi-{name}".to_string(),
+    )]);
+    if let Some(path) = path {
+        let data = fs::read(path).with_context(|| format!("Failed to read link map: {:?}", path))?;
+        let custom: LinkMap = serde_json::from_slice(&data)
+            .with_context(|| format!("Failed to parse link map: {:?}", path))?;
+        links.extend(custom);
+    }
+    Ok(links)
+}
+
+/// Turn references like `wasi:http/types@0.2.0` in doc text into markdown
+/// links for every namespace/package with a template in `links`. Fenced code
+/// blocks and references that are already part of a link are left alone.
+fn link_references(text: &str, links: &LinkMap) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.trim_start().starts_with("```") {
+            out.push_str(line);
+        } else {
+            link_line(line, links, &mut out);
+        }
+    }
+    out
+}
+
+fn link_line(line: &str, links: &LinkMap, out: &mut String) {
+    let is_id = |c: char| c.is_ascii_alphanumeric() || c == '-';
+    let mut rest = line;
+    while !rest.is_empty() {
+        let start = rest.find(|c: char| c.is_ascii_lowercase()).unwrap_or(rest.len());
+        let prev = rest[..start].chars().last().or_else(|| out.chars().last());
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+
+        let word_len = rest.find(|c: char| !is_id(c)).unwrap_or(rest.len());
+        // Mid-word, or inside a URL/link target: not a reference
+        let linkable = !prev.is_some_and(|c| is_id(c) || "/:@.[(".contains(c));
+        let reference = if linkable { parse_reference(rest) } else { None };
+        let Some(reference) = reference else {
+            out.push_str(&rest[..word_len]);
+            rest = &rest[word_len..];
+            continue;
+        };
+
+        let len = reference.len;
+        let template = links
+            .get(&format!("{}:{}", reference.namespace, reference.name))
+            .or_else(|| links.get(reference.namespace));
+        let Some(template) = template else {
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        };
+        let url = template
+            .replace("{namespace}", reference.namespace)
+            .replace("{name}", reference.name)
+            .replace("{interface}", reference.interface.unwrap_or_default())
+            .replace("{version}", reference.version.unwrap_or_default());
+
+        // A reference in a code span links the whole span
+        if prev == Some('`') && rest[len..].starts_with('`') {
+            out.pop();
+            out.push_str(&format!("[`{}`]({})", &rest[..len], url));
+            rest = &rest[len + 1..];
+        } else {
+            out.push_str(&format!("[{}]({})", &rest[..len], url));
+            rest = &rest[len..];
+        }
+    }
+}
+
+/// A `ns:pkg[/iface][@version]` reference found in doc text.
+struct WitReference<'a> {
+    /// Length of the reference in the source text
+    len: usize,
+    namespace: &'a str,
+    name: &'a str,
+    interface: Option<&'a str>,
+    version: Option<&'a str>,
+}
+
+/// Match a reference at the start of `s`.
+fn parse_reference(s: &str) -> Option<WitReference<'_>> {
+    let id_len = |s: &str| {
+        let len = s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-')).unwrap_or(s.len());
+        (len > 0 && s.starts_with(|c: char| c.is_ascii_lowercase())).then_some(len)
+    };
+    let ns_len = id_len(s)?;
+    let rest = s[ns_len..].strip_prefix(':')?;
+    let name_len = id_len(rest)?;
+    let mut len = ns_len + 1 + name_len;
+
+    let mut interface = None;
+    if let Some(after) = s[len..].strip_prefix('/')
+        && let Some(iface_len) = id_len(after)
+    {
+        interface = Some(&after[..iface_len]);
+        len += 1 + iface_len;
+    }
+    let mut version = None;
+    if let Some(after) = s[len..].strip_prefix('@') {
+        let ver_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || ".+-".contains(c)))
+            .unwrap_or(after.len());
+        // Don't swallow the full stop ending a sentence
+        let ver = after[..ver_len].trim_end_matches('.');
+        if ver.starts_with(|c: char| c.is_ascii_digit()) {
+            version = Some(ver);
+            len += 1 + ver.len();
+        }
+    }
+    Some(WitReference {
+        len,
+        namespace: &s[..ns_len],
+        name: &rest[..name_len],
+        interface,
+        version,
+    })
 }
 
-fn display_wit_with_docs(docs: &Value, args: &Args) -> Result<()> {
+/// The component's WIT, printed by `wasm-tools`, with the docs added. With
+/// `--subcomponent`, the WIT is that of the nested component, whose bytes
+/// are given to `wasm-tools` on stdin.
+fn wit_with_docs(docs: &Value, ext: &Value, args: &Args, world: Option<&str>) -> Result<String> {
     // First, get the original WIT text from the component
-    let output = Command::new("wasm-tools")
-        .args(&["component", "wit", &args.component.to_string_lossy()])
-        .output()
-        .context("Failed to run wasm-tools component wit")?;
+    let output = match &args.subcomponent {
+        Some(spec) => {
+            let file_bytes = map_file(&args.component)?;
+            let bytes = find_subcomponent(&file_bytes, args, spec)?.bytes;
+            let mut child = Command::new("wasm-tools")
+                .args(["component", "wit"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("Failed to run wasm-tools component wit")?;
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+            // Written from another thread, so that a large WIT printed to
+            // stdout can't block wasm-tools before it has read its input.
+            let (written, output) = std::thread::scope(|scope| {
+                let writer = scope.spawn(move || stdin.write_all(bytes));
+                let output = child.wait_with_output();
+                (writer.join().expect("writing to wasm-tools doesn't panic"), output)
+            });
+            let output = output.context("Failed to run wasm-tools component wit")?;
+            // A failing wasm-tools may stop reading early; its own error,
+            // reported below, says more than the broken pipe.
+            if output.status.success() {
+                written.context("Failed to pass the subcomponent to wasm-tools component wit")?;
+            }
+            output
+        }
+        None => Command::new("wasm-tools")
+            .args(["component", "wit", &args.component.to_string_lossy()])
+            .output()
+            .context("Failed to run wasm-tools component wit")?,
+    };
     
     if !output.status.success() {
         anyhow::bail!("wasm-tools component wit failed: {}", String::from_utf8_lossy(&output.stderr));
@@ -232,92 +2782,287 @@ fn display_wit_with_docs(docs: &Value, args: &Args) -> Result<()> {
         .context("Failed to parse wasm-tools output as UTF-8")?;
     
     // Parse and inject documentation
-    let enhanced_wit = inject_docs_into_wit(&wit_text, docs)?;
-    println!("{}", enhanced_wit);
-    
-    Ok(())
+    let package = ext.get("package").and_then(|p| p.as_str());
+    inject_docs_into_wit(&wit_text, docs, package, world)
+}
+
+const WIT_KEYWORDS: &[&str] = &[
+    "package", "world", "interface", "import", "export", "use", "include", "with", "as", "from", "func", "async",
+    "type", "record", "variant", "enum", "flags", "resource", "constructor", "static", "borrow", "own",
+];
+
+const WIT_TYPES: &[&str] = &[
+    "bool", "char", "string", "u8", "u16", "u32", "u64", "s8", "s16", "s32", "s64", "f32", "f64", "list", "option",
+    "result", "tuple", "future", "stream", "error-context",
+];
+
+const DOC_COMMENT_COLOR: &str = "\x1b[32m";
+const COMMENT_COLOR: &str = "\x1b[2m";
+const KEYWORD_COLOR: &str = "\x1b[1;35m";
+const TYPE_COLOR: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to color the output, as `--color` asks.
+fn colors(args: &Args) -> bool {
+    match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            !capturing() && std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+        }
+    }
+}
+
+/// `text` with its WIT syntax colored with ANSI escapes: keywords, built-in
+/// types, and doc comments apart from other comments.
+fn highlight_wit(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    for line in text.split_inclusive('\n') {
+        let (line, newline) = match line.strip_suffix('\n') {
+            Some(line) => (line, "\n"),
+            None => (line, ""),
+        };
+        let mut rest = line;
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with("//") {
+                let color = if rest.starts_with("///") { DOC_COMMENT_COLOR } else { COMMENT_COLOR };
+                out.push_str(&format!("{}{}{}", color, rest, RESET));
+                break;
+            }
+            // Identifiers may contain `-`; `%` escapes keywords.
+            if c.is_ascii_alphabetic() || c == '%' {
+                let end = rest[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                    .map_or(rest.len(), |end| end + 1);
+                let word = &rest[..end];
+                match word {
+                    _ if WIT_KEYWORDS.contains(&word) => out.push_str(&format!("{}{}{}", KEYWORD_COLOR, word, RESET)),
+                    _ if WIT_TYPES.contains(&word) => out.push_str(&format!("{}{}{}", TYPE_COLOR, word, RESET)),
+                    _ => out.push_str(word),
+                }
+                rest = &rest[end..];
+                continue;
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        out.push_str(newline);
+    }
+    out
 }
 
-fn inject_docs_into_wit(wit_text: &str, docs: &Value) -> Result<String> {
+fn inject_docs_into_wit(wit_text: &str, docs: &Value, package: Option<&str>, target_world: Option<&str>) -> Result<String> {
+    // With several worlds printed, each must match its docs by name; guessing
+    // is only safe when there's a single world (e.g. a component's `root`).
+    let single_world = wit_text
+        .lines()
+        .filter(|line| parse_world_header(line.trim()).is_some())
+        .count()
+        == 1;
+    let interfaces = docs.get("interfaces").and_then(|i| i.as_object());
+
     let mut result = String::new();
-    let lines: Vec<&str> = wit_text.lines().collect();
-    let mut i = 0;
-    
-    while i < lines.len() {
-        let line = lines[i].trim();
-        
-        // Look for world definitions
-        if line.starts_with("world ") {
-            let world_name = extract_world_name(line);
-            
-            // Add world documentation before the world declaration
-            if let Some(world_docs) = get_world_docs(docs, &world_name) {
-                for doc_line in world_docs.lines() {
-                    result.push_str(&format!("/// {}\n", doc_line));
+    // The blocks enclosing the current line, innermost last.
+    let mut scopes: Vec<WitScope> = Vec::new();
+    // Set by a `package ns:pkg;` header: whether top-level interfaces are
+    // the documented ones. Without a recorded package, only such a header
+    // is trusted (a binary WIT package prints its own package that way).
+    let mut top_level_documented = false;
+
+    for line in wit_text.lines() {
+        let trimmed = line.trim();
+        let mut opened = WitScope::Other;
+
+        let item_docs = match scopes.last() {
+            None | Some(WitScope::Package(_)) => {
+                let in_documented_package = match scopes.last() {
+                    Some(WitScope::Package(documented)) => *documented,
+                    _ => top_level_documented,
+                };
+                if let Some((name, block)) = parse_package_header(trimmed) {
+                    let documented = package.map_or(!block, |p| same_versioned_name(p, &name));
+                    if block {
+                        opened = WitScope::Package(documented);
+                    } else {
+                        top_level_documented = documented;
+                    }
+                    None
+                } else if let Some(world_name) = parse_world_header(trimmed) {
+                    let world_docs = find_world(docs, &world_name, target_world, single_world);
+                    opened = WitScope::World(world_docs);
+                    world_docs.and_then(doc_text)
+                } else if let Some(name) = parse_interface_header(trimmed) {
+                    let iface = interfaces
+                        .filter(|_| in_documented_package)
+                        .and_then(|i| find_key(i, &name))
+                        .map(|(_, iface)| iface);
+                    opened = WitScope::Interface(iface);
+                    iface.and_then(doc_text)
+                } else {
+                    None
                 }
             }
-            
-            result.push_str(lines[i]);
-            result.push('\n');
-            i += 1;
-            
-            // Process the world body
-            while i < lines.len() {
-                let current_line = lines[i];
-                let trimmed = current_line.trim();
-                
-                // Check if this is an export/import function
-                if trimmed.starts_with("export ") || trimmed.starts_with("import ") {
-                    if let Some(func_name) = extract_function_name(trimmed) {
-                        // Add function documentation before the function declaration
-                        if let Some(func_docs) = get_function_docs(docs, &world_name, &func_name) {
-                            let indent = get_indent(current_line);
-                            for doc_line in func_docs.lines() {
-                                result.push_str(&format!("{}/// {}\n", indent, doc_line));
-                            }
-                        }
+            Some(WitScope::World(Some(world_docs))) => match parse_world_item(trimmed) {
+                Some(WitItem::Named { direction, name, kind }) => {
+                    if kind == "interface" {
+                        let iface = find_item(world_docs, interface_maps(direction), &name);
+                        opened = WitScope::Interface(iface);
+                        iface.and_then(doc_text)
+                    } else {
+                        function_docs(world_docs, function_maps(direction), &name, None, trimmed)
                     }
                 }
-                
-                result.push_str(current_line);
-                result.push('\n');
-                i += 1;
-                
-                // Stop when we reach the end of the world
-                if trimmed == "}" {
-                    break;
+                // Docs injected before world interfaces were recorded still
+                // have the package's own interfaces.
+                Some(WitItem::Path { direction, path }) => find_interface(world_docs, interface_maps(direction), &path)
+                    .or_else(|| package_interface(docs, package, &path))
+                    .and_then(doc_text),
+                None => container_member_docs(docs, package, world_docs, trimmed, &mut opened),
+            },
+            Some(WitScope::Interface(Some(iface))) => container_member_docs(docs, package, iface, trimmed, &mut opened),
+            Some(WitScope::Resource { name, container: Some(container) }) => {
+                let funcs = &["funcs"][..];
+                match parse_resource_member(trimmed) {
+                    Some((_, "constructor")) => {
+                        find_item(container, funcs, &format!("[constructor]{}", name)).and_then(doc_text)
+                    }
+                    Some((member, kind)) => function_docs(container, funcs, &member, Some((name.as_str(), kind)), trimmed),
+                    None => None,
                 }
             }
-        } else {
-            result.push_str(lines[i]);
-            result.push('\n');
-            i += 1;
+            Some(WitScope::Type(Some(ty))) => parse_identifier(trimmed).and_then(|member| {
+                let items = ty.get("items").and_then(|i| i.as_object())?;
+                find_key(items, &member).and_then(|(_, docs)| docs.as_str()).map(String::from)
+            }),
+            Some(_) => None,
+        };
+
+        if let Some(item_docs) = item_docs {
+            let indent = get_indent(line);
+            for doc_line in item_docs.lines() {
+                if doc_line.is_empty() {
+                    result.push_str(&format!("{}///\n", indent));
+                } else {
+                    result.push_str(&format!("{}/// {}\n", indent, doc_line));
+                }
+            }
+        }
+        result.push_str(line);
+        result.push('\n');
+
+        let opens = trimmed.matches('{').count();
+        let closes = trimmed.matches('}').count();
+        if opens > closes {
+            scopes.push(opened);
+        }
+        for _ in opens..closes {
+            scopes.pop();
         }
     }
-    
+
     Ok(result)
 }
 
-fn extract_world_name(line: &str) -> String {
-    // Extract world name from "world <name> {" pattern
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    if parts.len() >= 2 {
-        parts[1].to_string()
+/// What a `{ ... }` block of printed WIT belongs to, with the docs for its
+/// items where they were found.
+enum WitScope<'a> {
+    /// `package ns:pkg { ... }`, and whether it is the documented package
+    Package(bool),
+    World(Option<&'a Value>),
+    Interface(Option<&'a Value>),
+    /// A resource, and the interface or world that documents its functions
+    Resource { name: String, container: Option<&'a Value> },
+    /// A record, variant, enum or flags type
+    Type(Option<&'a Value>),
+    Other,
+}
+
+/// An `import`/`export` statement inside a printed world.
+enum WitItem {
+    /// `import name: func(...)` or `export name: interface {`
+    Named { direction: &'static str, name: String, kind: &'static str },
+    /// `import ns:pkg/iface@1.0.0;`
+    Path { direction: &'static str, path: String },
+}
+
+/// `package ns:pkg@1.0.0;` or, for one of several packages in a file,
+/// `package ns:pkg@1.0.0 {`: the package name, and whether it opens a block.
+fn parse_package_header(line: &str) -> Option<(String, bool)> {
+    let rest = line.strip_prefix("package ")?;
+    let block = rest.trim_end().ends_with('{');
+    let name = rest.trim_end_matches(['{', ';', ' ']).trim();
+    (!name.is_empty()).then(|| (name.to_string(), block))
+}
+
+/// Extract the world name from `world <name> {`, tolerating package
+/// qualification and versions (`world wasi:http/proxy@0.2.0 {`,
+/// `world imports @0.2.0 {`).
+fn parse_world_header(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("world ")?;
+    let name = rest.split(|c: char| c.is_whitespace() || c == '{').next()?;
+    let name = name.split('@').next()?;
+    let name = name.rsplit('/').next()?;
+    let name = name.trim_start_matches('%');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The name in `interface <name> {`.
+fn parse_interface_header(line: &str) -> Option<String> {
+    parse_identifier(line.strip_prefix("interface ")?)
+}
+
+fn parse_world_item(line: &str) -> Option<WitItem> {
+    let (direction, rest) = if let Some(rest) = line.strip_prefix("import ") {
+        ("imports", rest)
     } else {
-        "unknown".to_string()
+        ("exports", line.strip_prefix("export ")?)
+    };
+    match parse_named_item(rest) {
+        Some((name, kind)) => Some(WitItem::Named { direction, name, kind }),
+        None => {
+            let path = rest.trim().trim_end_matches(';').trim();
+            (!path.is_empty()).then(|| WitItem::Path { direction, path: path.to_string() })
+        }
     }
 }
 
-fn extract_function_name(line: &str) -> Option<String> {
-    // Extract function name from "export/import <name>: func(...)" pattern
-    if let Some(colon_pos) = line.find(':') {
-        let before_colon = &line[..colon_pos];
-        let parts: Vec<&str> = before_colon.split_whitespace().collect();
-        if parts.len() >= 2 {
-            return Some(parts[1].to_string());
+/// Parse `name: func(...)`, `name: interface {`, or a type definition such as
+/// `record name {`, returning the item name and what kind of item it is.
+fn parse_named_item(line: &str) -> Option<(String, &'static str)> {
+    for keyword in ["type", "record", "variant", "enum", "flags", "resource"] {
+        if let Some(rest) = line.strip_prefix(keyword).and_then(|r| r.strip_prefix(' ')) {
+            let name = rest.split(|c: char| c.is_whitespace() || c == '{' || c == ';' || c == '=').next()?;
+            let kind = if keyword == "resource" { "resource" } else { "type" };
+            return Some((name.trim_start_matches('%').to_string(), kind));
         }
     }
-    None
+    // Named items are `name: ...`; a colon without a following space is part
+    // of a package path like `wasi:io/poll`.
+    let (name, rest) = line.split_once(": ")?;
+    if name.contains(char::is_whitespace) || name.contains('/') {
+        return None;
+    }
+    let kind = if rest.starts_with("interface") { "interface" } else { "func" };
+    Some((name.trim_start_matches('%').to_string(), kind))
+}
+
+/// Parse a function of a printed resource: `constructor(...)`,
+/// `name: static func(...)` or `name: func(...)`.
+fn parse_resource_member(line: &str) -> Option<(String, &'static str)> {
+    if line.starts_with("constructor(") {
+        return Some((String::new(), "constructor"));
+    }
+    let (name, rest) = line.split_once(": ")?;
+    let kind = if rest.starts_with("static ") { "static" } else { "method" };
+    Some((name.trim_start_matches('%').to_string(), kind))
+}
+
+/// The identifier a line starts with: a field (`name: type,`), case
+/// (`name(type),`, `name,`) or flag.
+fn parse_identifier(line: &str) -> Option<String> {
+    let line = line.trim_start().trim_start_matches('%');
+    let end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(line.len());
+    (end > 0).then(|| line[..end].to_string())
 }
 
 fn get_indent(line: &str) -> &str {
@@ -325,47 +3070,234 @@ fn get_indent(line: &str) -> &str {
     &line[..line.len() - trimmed_len]
 }
 
-fn get_world_docs(docs: &Value, world_name: &str) -> Option<String> {
+fn find_world<'a>(docs: &'a Value, world_name: &str, target_world: Option<&str>, single_world: bool) -> Option<&'a Value> {
     let worlds = docs.get("worlds").and_then(|w| w.as_object())?;
-    
-    // First try exact match
-    if let Some(world) = worlds.get(world_name) {
-        return world.get("docs").and_then(|d| d.as_str()).map(|s| s.to_string());
+
+    // First try a match by name
+    if let Some((_, world)) = find_key(worlds, world_name) {
+        return Some(world);
     }
-    
+    if !single_world {
+        return None;
+    }
+
+    // Then the world selected with `--world` or recorded at injection time
+    if let Some(target) = target_world {
+        return worlds.get(target);
+    }
+
     // If no exact match and there's only one world, use that
     if worlds.len() == 1 {
-        let (_, world_data) = worlds.iter().next().unwrap();
-        return world_data.get("docs").and_then(|d| d.as_str()).map(|s| s.to_string());
+        return worlds.values().next();
     }
-    
+
     None
 }
 
-fn get_function_docs(docs: &Value, world_name: &str, func_name: &str) -> Option<String> {
-    let worlds = docs.get("worlds").and_then(|w| w.as_object())?;
-    
-    // First try exact world match
-    if let Some(world) = worlds.get(world_name) {
-        return get_function_docs_from_world(world, func_name);
+/// Maps of a world's docs that hold functions for `direction`. Exports only
+/// land in the shared map when no import has the same name (legacy keys such
+/// as `functions` are normalized away when decoding).
+fn function_maps(direction: &str) -> &'static [&'static str] {
+    if direction == "exports" {
+        &["func_exports", "funcs"]
+    } else {
+        &["funcs"]
     }
-    
-    // If no exact match and there's only one world, use that
-    if worlds.len() == 1 {
-        let (_, world_data) = worlds.iter().next().unwrap();
-        return get_function_docs_from_world(world_data, func_name);
+}
+
+fn interface_maps(direction: &str) -> &'static [&'static str] {
+    if direction == "exports" {
+        &["interface_exports", "interfaces"]
+    } else {
+        &["interfaces"]
     }
-    
-    None
 }
 
-fn get_function_docs_from_world(world: &Value, func_name: &str) -> Option<String> {
-    // Try both func_exports and functions for backward compatibility
-    world.get("func_exports")
-        .or_else(|| world.get("functions"))
-        .and_then(|funcs| funcs.as_object())
-        .and_then(|functions| functions.get(func_name))
-        .and_then(|func| func.get("docs"))
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string())
+fn find_item<'a>(container: &'a Value, maps: &[&str], name: &str) -> Option<&'a Value> {
+    maps.iter()
+        .find_map(|map| find_key(container.get(*map)?.as_object()?, name))
+        .map(|(_, item)| item)
+}
+
+/// An interface imported or exported by its full name, which the printer
+/// may spell with or without its version.
+fn find_interface<'a>(container: &'a Value, maps: &[&str], path: &str) -> Option<&'a Value> {
+    maps.iter().find_map(|map| {
+        let entries = container.get(*map)?.as_object()?;
+        entries.iter().find(|(key, _)| key.contains('/') && same_versioned_name(key, path)).map(|(_, item)| item)
+    })
+}
+
+/// Docs of the function printed as `name` on `line`, under its docs key:
+/// `name`, `[method]r.name` or `[static]r.name` for a function of resource
+/// `r`, with `[async]`, `[async method]`, ... for async functions.
+fn function_docs(container: &Value, maps: &[&str], name: &str, resource: Option<(&str, &str)>, line: &str) -> Option<String> {
+    let is_async = line.contains(" async func");
+    let key = match (resource, is_async) {
+        (Some((resource, kind)), false) => format!("[{}]{}.{}", kind, resource, name),
+        (Some((resource, kind)), true) => format!("[async {}]{}.{}", kind, resource, name),
+        (None, false) => name.to_string(),
+        (None, true) => format!("[async]{}", name),
+    };
+    find_item(container, maps, &key)
+        .or_else(|| is_async.then(|| find_item(container, maps, &key.replacen("async ", "", 1).replacen("[async]", "", 1))).flatten())
+        .and_then(doc_text)
+}
+
+/// The interface of the documented package `package` that a `use` or
+/// `import` path names: `iface` within the package, or `ns:pkg/iface@1.0.0`.
+fn package_interface<'a>(docs: &'a Value, package: Option<&str>, path: &str) -> Option<&'a Value> {
+    let name = match path.split_once('/') {
+        None => path,
+        Some((pkg, name)) => {
+            let version = path.split_once('@').map(|(_, v)| format!("@{}", v)).unwrap_or_default();
+            if !package.is_some_and(|p| same_versioned_name(p, &format!("{}{}", pkg, version))) {
+                return None;
+            }
+            name.split('@').next()?
+        }
+    };
+    find_key(docs.get("interfaces")?.as_object()?, name).map(|(_, iface)| iface)
+}
+
+/// Docs for a function or type declared directly inside an interface or
+/// world, noting in `opened` the block a resource or type body opens.
+fn container_member_docs<'a>(
+    docs: &Value,
+    package: Option<&str>,
+    container: &'a Value,
+    line: &str,
+    opened: &mut WitScope<'a>,
+) -> Option<String> {
+    if let Some((path, uses)) = parse_use(line) {
+        return use_docs(package_interface(docs, package, &path), container, &uses);
+    }
+    let (name, kind) = parse_named_item(line)?;
+    match kind {
+        "resource" => {
+            *opened = WitScope::Resource { name: name.clone(), container: Some(container) };
+            find_item(container, &["types"], &name).and_then(doc_text)
+        }
+        "type" => {
+            let ty = find_item(container, &["types"], &name);
+            *opened = WitScope::Type(ty);
+            ty.and_then(doc_text)
+        }
+        _ => function_docs(container, &["funcs"], &name, None, line),
+    }
+}
+
+/// The interface a `use iface.{a, b as c};` line names, and the names it
+/// brings in as `(original, local)` pairs: `("a", "a")` and `("b", "c")`.
+fn parse_use(line: &str) -> Option<(String, Vec<(String, String)>)> {
+    let rest = line.strip_prefix("use ")?;
+    let (path, names) = rest.split_once(".{")?;
+    let names = names.split('}').next()?;
+    let pairs = names
+        .split(',')
+        .filter_map(|name| {
+            let name = name.trim();
+            let (original, local) = name.split_once(" as ").unwrap_or((name, name));
+            let (original, local) = (original.trim().trim_start_matches('%'), local.trim().trim_start_matches('%'));
+            (!local.is_empty()).then(|| (original.to_string(), local.to_string()))
+        })
+        .collect();
+    Some((path.trim().trim_start_matches('%').to_string(), pairs))
+}
+
+/// Docs for a `use` line: those of the first type it brings in that has
+/// any. A type is looked up under its original name in `source`, the
+/// interface it comes from when that is documented, then in `container`
+/// under its local name and its original one, which is how docs from
+/// other tools may key it.
+fn use_docs(source: Option<&Value>, container: &Value, uses: &[(String, String)]) -> Option<String> {
+    uses.iter().find_map(|(original, local)| {
+        let lookup = |container: &Value, name: &str| find_item(container, &["types"], name).and_then(doc_text);
+        source
+            .and_then(|source| lookup(source, original))
+            .or_else(|| lookup(container, local))
+            .or_else(|| lookup(container, original))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIT: &str = "package acme:shop@1.0.0;
+
+interface types {
+  record request {
+    id: u32,
+  }
+}
+
+interface consumer {
+  use types.{request as http-request};
+  use acme:shop/types@1.0.0.{request as order-request};
+}
+";
+
+    #[test]
+    fn renamed_use_shows_the_docs_of_the_source_interface() {
+        let docs = json!({
+            "interfaces": {
+                "types": { "types": { "request": { "docs": "An incoming request." } } },
+                "consumer": { "types": { "http-request": { "docs": "The consumer's own docs." } } },
+            }
+        });
+        let wit = inject_docs_into_wit(WIT, &docs, Some("acme:shop@1.0.0"), None).unwrap();
+        assert!(wit.contains("  /// An incoming request.\n  use types.{request as http-request};"), "{}", wit);
+        assert!(wit.contains("  /// An incoming request.\n  use acme:shop/types@1.0.0.{request as order-request};"), "{}", wit);
+    }
+
+    #[test]
+    fn renamed_use_falls_back_to_the_local_entry() {
+        let docs = json!({
+            "interfaces": {
+                "types": { "types": { "request": {} } },
+                "consumer": { "types": { "http-request": { "docs": "The consumer's own docs." } } },
+            }
+        });
+        let wit = inject_docs_into_wit(WIT, &docs, Some("acme:shop@1.0.0"), None).unwrap();
+        assert!(wit.contains("  /// The consumer's own docs.\n  use types.{request as http-request};"), "{}", wit);
+    }
+
+    #[test]
+    fn use_from_another_package_has_no_source_docs() {
+        let docs = json!({ "interfaces": { "types": { "types": { "request": { "docs": "An incoming request." } } } } });
+        let wit = WIT.replace("acme:shop/", "acme:other/");
+        let wit = inject_docs_into_wit(&wit, &docs, Some("acme:shop@1.0.0"), None).unwrap();
+        assert!(wit.contains("  use types.{request as http-request};\n  use acme:other"), "{}", wit);
+    }
+
+    #[test]
+    fn only_web_mail_and_relative_urls_are_linked() {
+        for url in ["https://acme.example/a", "HTTP://acme.example", "mailto:dev@acme.example", "#acme", "a.html"] {
+            assert!(is_safe_url(url), "{}", url);
+        }
+        for url in ["javascript:alert(1)", " JaVa\tScript:alert(1)", "vbscript:x", "data:text/html,<b>", "file:///"] {
+            assert!(!is_safe_url(url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn signature_types_link_to_their_docs() {
+        let target = |name: &str, container: &str| TypeTarget {
+            name: name.to_string(),
+            container: Some(container.to_string()),
+            path: format!("{}#{}", container, name),
+        };
+        let types = [
+            target("request", "acme:shop/types"),
+            target("request", "acme:shop/admin"),
+            target("id", "acme:shop/admin"),
+        ];
+        let html = linked_signature("func(request: request, id: id) -> list<u8>", Some("acme:shop/types"), &types);
+        assert_eq!(
+            html,
+            "func(request: <a href=\"#acme-shop-types-request\">request</a>, \
+             id: <a href=\"#acme-shop-admin-id\">id</a>) -&gt; list&lt;u8&gt;"
+        );
+    }
 }
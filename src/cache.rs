@@ -0,0 +1,147 @@
+//! On-disk cache of the docs sections built from a docs source.
+//!
+//! Entries live in `<cache-dir>/<key>/`, one file per section. The key hashes
+//! every file of the docs source (including `deps/`) together with the
+//! options that affect the output, so editing any WIT file or changing
+//! `--world` results in a fresh entry. Stale entries are never reused, only
+//! left behind; deleting the directory is always safe. The key is a SHA-256,
+//! so it stays the same across Rust releases.
+//!
+//! `--generate-missing-cmd` counts by its command line only: the script it
+//! runs, and whatever that script consults, are not part of the key.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{Args, DocsSections};
+
+const PACKAGE_DOCS_FILE: &str = "package-docs";
+const EXT_FILE: &str = "package-docs-ext.json";
+const WIT_FILE: &str = "package.wit";
+
+/// The cache key for the docs built from `args`. Guest sources read with
+/// `--cargo-component` or `--guest-docs`, the `--patch` file and the
+/// configuration file holding package metadata count as part of the docs
+/// source.
+pub fn key(args: &Args) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let options = json!([
+        env!("CARGO_PKG_VERSION"),
+        args.world,
+        args.whole_package,
+        args.embed_wit,
+        args.defines,
+        args.stub_missing,
+        args.generate_missing_cmd,
+        args.include_internal,
+        args.internal_tag,
+        args.include,
+        args.exclude,
+        args.authors,
+        args.license,
+        args.homepage,
+        args.repository,
+        args.meta,
+    ]);
+    update(&mut hasher, &serde_json::to_vec(&options)?);
+
+    let files = match (&args.wit_dir, &args.wit_package) {
+        (Some(wit_dir), _) if wit_dir.is_dir() => source_files(wit_dir)?,
+        (Some(wit_file), _) => {
+            // A single file is parsed together with a sibling `deps/`.
+            let mut files = vec![wit_file.clone()];
+            let deps = wit_file.parent().unwrap_or(Path::new(".")).join("deps");
+            if deps.is_dir() {
+                files.extend(source_files(&deps)?);
+            }
+            files
+        }
+        (None, Some(wit_package)) => vec![wit_package.clone()],
+        (None, None) => unreachable!("clap requires a docs source"),
+    };
+    let mut files = files;
+    for (lang, project) in crate::guest_projects(args) {
+        files.extend(crate::guest_docs::source_files(lang, &project)?);
+    }
+    let files = match &args.patch {
+        Some(patch) => [files, vec![patch.clone()]].concat(),
+        None => files,
+    };
+    let files = match crate::metadata::config_path(args) {
+        Some(config) => [files, vec![config]].concat(),
+        None => files,
+    };
+    for file in files {
+        update(&mut hasher, file.to_string_lossy().as_bytes());
+        update(&mut hasher, &fs::read(&file).with_context(|| format!("reading {:?}", file))?);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Feed `bytes` to `hasher` with their length first, so that consecutive
+/// pieces can't run into each other.
+fn update(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+/// All files below `dir`, in a stable order.
+fn source_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("reading {:?}", dir))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+
+    let mut files = Vec::new();
+    for entry in entries {
+        if entry.is_dir() {
+            files.extend(source_files(&entry)?);
+        } else {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+/// The cached sections for `key`, if there's a complete entry.
+pub fn load(cache_dir: &Path, key: &str) -> Result<Option<DocsSections>> {
+    let entry = cache_dir.join(key);
+    if !entry.join(EXT_FILE).is_file() {
+        return Ok(None);
+    }
+    let read = |name: &str| fs::read(entry.join(name)).with_context(|| format!("reading cache entry {:?}", entry));
+    let wit = match entry.join(WIT_FILE).is_file() {
+        true => Some(read(WIT_FILE)?),
+        false => None,
+    };
+    Ok(Some(DocsSections {
+        package_docs: read(PACKAGE_DOCS_FILE)?,
+        ext: read(EXT_FILE)?,
+        wit,
+    }))
+}
+
+/// Save `docs` under `key`. The entry is written to a temporary directory
+/// and renamed into place, so concurrent runs never see a partial entry.
+pub fn store(cache_dir: &Path, key: &str, docs: &DocsSections) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let tmp = cache_dir.join(format!("{}.tmp-{}", key, std::process::id()));
+    fs::create_dir_all(&tmp)?;
+    fs::write(tmp.join(PACKAGE_DOCS_FILE), &docs.package_docs)?;
+    if let Some(wit) = &docs.wit {
+        fs::write(tmp.join(WIT_FILE), wit)?;
+    }
+    fs::write(tmp.join(EXT_FILE), &docs.ext)?;
+
+    if fs::rename(&tmp, cache_dir.join(key)).is_err() {
+        // Another run stored the same entry first.
+        fs::remove_dir_all(&tmp)?;
+    }
+    Ok(())
+}
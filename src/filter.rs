@@ -0,0 +1,268 @@
+//! `--include`/`--exclude`: which items get their docs embedded.
+//!
+//! Patterns are globs over item paths as `wit-docs-view` shows them
+//! (`wasi:http/types@0.2.0#fields.get`), where `*` matches any run of
+//! characters and `?` any single one. A pattern without a version matches
+//! items of any version. An item is matched when it, or the world, interface,
+//! resource or type it belongs to, is: `--exclude '*/internal-*'` leaves out
+//! those interfaces with everything in them. Items that aren't embedded lose
+//! their docs, not their place in the package.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use tracing::debug;
+use wit_docs_inject::{qualify, resource_of};
+use wit_parser::{PackageId, PackageMetadata, Resolve};
+
+use crate::template;
+
+/// The `--include` and `--exclude` patterns.
+pub struct Filter<'a> {
+    pub include: &'a [String],
+    pub exclude: &'a [String],
+}
+
+impl Filter<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether the docs of the item at `path` are embedded: it or one of
+    /// the items it belongs to matches an `--include` (when there are any),
+    /// and none matches an `--exclude`.
+    pub fn keeps(&self, path: &str) -> bool {
+        let lineage = lineage(path);
+        let matched = |patterns: &[String]| {
+            patterns.iter().any(|pattern| lineage.iter().any(|path| matches(pattern, path)))
+        };
+        (self.include.is_empty() || matched(self.include)) && !matched(self.exclude)
+    }
+
+    /// Drop the docs of the items of `pkg` the filter doesn't keep. Returns
+    /// how many were dropped.
+    pub fn apply(&self, resolve: &mut Resolve, pkg: PackageId) -> Result<usize> {
+        let package = resolve.packages[pkg].name.to_string();
+        let mut model = serde_json::to_value(PackageMetadata::extract(resolve, pkg))?;
+        let mut dropped = 0;
+        for (name, iface) in model.get_mut("interfaces").and_then(|i| i.as_object_mut()).into_iter().flatten() {
+            let path = qualify(Some(&package), name);
+            dropped += self.container(iface, &path, &format!("{}#", path));
+        }
+        for (name, world) in model.get_mut("worlds").and_then(|w| w.as_object_mut()).into_iter().flatten() {
+            let path = qualify(Some(&package), name);
+            dropped += self.container(world, &path, &format!("{}#", path));
+            for map in ["interfaces", "interface_exports"] {
+                for (iface_name, iface) in world.get_mut(map).and_then(|i| i.as_object_mut()).into_iter().flatten() {
+                    let iface_path = format!("{}#{}", path, iface_name);
+                    dropped += self.container(iface, &iface_path, &format!("{}.", iface_path));
+                }
+            }
+        }
+        debug!(dropped, "filtered docs");
+
+        let filtered: PackageMetadata = serde_json::from_value(model).context("re-reading the filtered docs")?;
+        // Injecting only sets docs, so clear them first for removals to stick.
+        template::for_each_docs(resolve, pkg, |docs| docs.contents = None);
+        filtered.inject(resolve, pkg).context("re-injecting the filtered docs")?;
+        Ok(dropped)
+    }
+
+    /// Drop the docs the filter doesn't keep from the world or interface
+    /// `container` at `path`, and from its functions and types, whose paths
+    /// start with `prefix`.
+    fn container(&self, container: &mut Value, path: &str, prefix: &str) -> usize {
+        let mut dropped = 0;
+        if !self.keeps(path) {
+            dropped += clear_docs(container);
+        }
+        for map in ["funcs", "func_exports"] {
+            for (name, func) in container.get_mut(map).and_then(|f| f.as_object_mut()).into_iter().flatten() {
+                if !self.keeps(&format!("{}{}", prefix, name)) {
+                    dropped += clear_docs(func);
+                }
+            }
+        }
+        for (name, ty) in container.get_mut("types").and_then(|t| t.as_object_mut()).into_iter().flatten() {
+            let ty_path = format!("{}{}", prefix, name);
+            if !self.keeps(&ty_path) {
+                dropped += clear_docs(ty);
+            }
+            if let Some(items) = ty.get_mut("items").and_then(|i| i.as_object_mut()) {
+                let before = items.len();
+                items.retain(|member, _| self.keeps(&format!("{}.{}", ty_path, member)));
+                dropped += before - items.len();
+            }
+        }
+        dropped
+    }
+}
+
+/// Remove the docs of one item of the docs model: functions with docs only
+/// are a plain string, everything else an object with a `docs` field.
+fn clear_docs(item: &mut Value) -> usize {
+    match item {
+        Value::String(_) => {
+            *item = Value::Null;
+            1
+        }
+        Value::Object(object) => usize::from(object.remove("docs").is_some()),
+        _ => 0,
+    }
+}
+
+/// `path` and the paths of the items it belongs to, outermost first:
+/// `ns:pkg/i@1.0.0#t.case` => `ns:pkg/i@1.0.0`, `ns:pkg/i@1.0.0#t`, itself.
+/// Resource functions belong to their resource.
+fn lineage(path: &str) -> Vec<String> {
+    let Some((container, rest)) = path.split_once('#') else {
+        return vec![path.to_string()];
+    };
+    let mut lineage = vec![container.to_string()];
+    let mut prefix = format!("{}#", container);
+    let mut segments = rest.split('.');
+    while let Some(segment) = segments.next() {
+        // `[method]r.m`, possibly in an interface of a world (`w#i.[static]r.m`).
+        if segment.starts_with('[') {
+            let func = segments.by_ref().fold(segment.to_string(), |func, s| format!("{}.{}", func, s));
+            if let Some(resource) = resource_of(&func) {
+                lineage.push(format!("{}{}", prefix, resource));
+            }
+            lineage.push(format!("{}{}", prefix, func));
+            break;
+        }
+        let item = format!("{}{}", prefix, segment);
+        prefix = format!("{}.", item);
+        lineage.push(item);
+    }
+    lineage
+}
+
+/// Whether the glob `pattern` matches the item `path`; without a version,
+/// it matches the path without one too.
+fn matches(pattern: &str, path: &str) -> bool {
+    glob(pattern, path) || (!pattern.contains('@') && glob(pattern, &without_version(path)))
+}
+
+/// `ns:pkg/i@1.0.0#f` => `ns:pkg/i#f`
+fn without_version(path: &str) -> String {
+    match path.split_once('@') {
+        Some((name, rest)) => match rest.find('#') {
+            Some(hash) => format!("{}{}", name, &rest[hash..]),
+            None => name.to_string(),
+        },
+        None => path.to_string(),
+    }
+}
+
+/// `*` matches any run of characters and `?` any one.
+fn glob(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*`: the pattern after it, and the
+    // text it has swallowed so far.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after, swallowed)) => {
+                    star = Some((after, swallowed + 1));
+                    p = after;
+                    t = swallowed + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter<'a>(include: &'a [String], exclude: &'a [String]) -> Filter<'a> {
+        Filter { include, exclude }
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn star_matches_any_run_of_characters() {
+        assert!(glob("*", ""));
+        assert!(glob("*", "wasi:http/types#fields"));
+        assert!(glob("wasi:*/types", "wasi:http/types"));
+        assert!(glob("*#fields.*", "wasi:http/types#fields.get"));
+        assert!(glob("w?si:*", "wasi:http"));
+        assert!(!glob("wasi:*/types", "wasi:http/handler"));
+        assert!(!glob("w?si:*", "wsi:http"));
+    }
+
+    #[test]
+    fn double_star_matches_like_a_single_one() {
+        assert!(glob("**", "wasi:http/types#fields.get"));
+        assert!(glob("wasi:**#get", "wasi:http/types#get"));
+        assert!(glob("a**b", "ab"));
+        assert!(!glob("wasi:**#get", "wasi:http/types#set"));
+    }
+
+    #[test]
+    fn patterns_without_a_version_match_any_version() {
+        assert_eq!(without_version("wasi:http/types@0.2.0#fields.get"), "wasi:http/types#fields.get");
+        assert_eq!(without_version("wasi:http/types@0.2.0"), "wasi:http/types");
+        assert_eq!(without_version("wasi:http/types#f"), "wasi:http/types#f");
+        assert!(matches("wasi:http/types#*", "wasi:http/types@0.2.0#fields"));
+        assert!(matches("wasi:http/types@0.2.0#*", "wasi:http/types@0.2.0#fields"));
+        assert!(!matches("wasi:http/types@0.3.0#*", "wasi:http/types@0.2.0#fields"));
+    }
+
+    #[test]
+    fn lineage_lists_the_enclosing_items() {
+        assert_eq!(lineage("ns:pkg/i@1.0.0"), ["ns:pkg/i@1.0.0"]);
+        assert_eq!(lineage("ns:pkg/i@1.0.0#t.case"), ["ns:pkg/i@1.0.0", "ns:pkg/i@1.0.0#t", "ns:pkg/i@1.0.0#t.case"]);
+        assert_eq!(lineage("ns:pkg/i#[method]r.get"), ["ns:pkg/i", "ns:pkg/i#r", "ns:pkg/i#[method]r.get"]);
+        assert_eq!(
+            lineage("ns:pkg/w#i.[static]r.new"),
+            ["ns:pkg/w", "ns:pkg/w#i", "ns:pkg/w#i.r", "ns:pkg/w#i.[static]r.new"]
+        );
+    }
+
+    #[test]
+    fn items_inherit_the_match_of_their_container() {
+        let exclude = patterns(&["*/internal-*"]);
+        let filter = filter(&[], &exclude);
+        assert!(!filter.keeps("ns:pkg/internal-state@1.0.0#counter.reset"));
+        assert!(!filter.keeps("ns:pkg/internal-state#[method]cache.get"));
+        assert!(filter.keeps("ns:pkg/api#internal-state"));
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let (include, exclude) = (patterns(&["ns:pkg/api"]), patterns(&["*#debug-*"]));
+        let filter = filter(&include, &exclude);
+        assert!(filter.keeps("ns:pkg/api@1.0.0#get"));
+        assert!(!filter.keeps("ns:pkg/api@1.0.0#debug-dump"));
+        assert!(!filter.keeps("ns:pkg/other#get"));
+    }
+
+    #[test]
+    fn empty_patterns() {
+        // No patterns keep everything.
+        let none = filter(&[], &[]);
+        assert!(none.is_empty());
+        assert!(none.keeps("ns:pkg/api#get"));
+        // An empty pattern matches no item.
+        let empty = patterns(&[""]);
+        assert!(!filter(&empty, &[]).keeps("ns:pkg/api#get"));
+        assert!(filter(&[], &empty).keeps("ns:pkg/api#get"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Harvest doc comments from the guest code of a component.
+//!
+//! Each supported language has an [`Adapter`] that scans the project's
+//! sources for the code implementing the world's exports (wit-bindgen trait
+//! implementations in Rust, exported functions and objects for jco,
+//! componentize-py classes in Python) and records the docs written there
+//! against the WIT items they implement. The docs are then used wherever the
+//! WIT itself has none.
+
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+use wit_parser::{Docs, Function, InterfaceId, Resolve, WorldId, WorldItem, WorldKey};
+
+use crate::{js_docs, py_docs, rust_docs};
+
+/// A guest language whose doc comments can be harvested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    /// Rust, as built by cargo-component
+    Rust,
+    /// JavaScript or TypeScript, as componentized by jco
+    Js,
+    /// Python, as built by componentize-py
+    Python,
+}
+
+impl Lang {
+    fn adapter(self) -> &'static dyn Adapter {
+        match self {
+            Lang::Rust => &rust_docs::Rust,
+            Lang::Js => &js_docs::Js,
+            Lang::Python => &py_docs::Python,
+        }
+    }
+}
+
+/// Parse a `--guest-docs` argument.
+pub fn parse_guest_docs(arg: &str) -> Result<(Lang, PathBuf), String> {
+    let (lang, dir) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected `lang=dir`, got `{}`", arg))?;
+    let lang = match lang.trim() {
+        "rust" => Lang::Rust,
+        "js" | "ts" | "javascript" | "typescript" => Lang::Js,
+        "python" | "py" => Lang::Python,
+        other => return Err(format!("unknown language `{}` (expected rust, js or python)", other)),
+    };
+    Ok((lang, PathBuf::from(dir)))
+}
+
+/// Reads the doc comments of one language's sources.
+pub trait Adapter {
+    /// The files of the project in `project` to scan, in a stable order.
+    fn source_files(&self, project: &Path) -> Result<Vec<PathBuf>>;
+
+    /// Record the docs in `text`, the contents of `file`, in `docs`.
+    fn scan(&self, file: &Path, text: &str, docs: &mut GuestDocs);
+}
+
+/// Which WIT item the code carrying some docs implements.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+    /// The world's exported functions
+    World,
+    /// An interface exported by name from the world (`export api: interface { ... }`)
+    NamedInterface(String),
+    /// An interface of a package (`ns:pkg/iface`)
+    PackageInterface { namespace: String, package: String, interface: String },
+    /// An interface the world exports, known only by its name, whether it's
+    /// exported by name or from a package
+    ExportedInterface(String),
+}
+
+/// Docs found on the code implementing one [`Target`].
+#[derive(Default)]
+pub struct ImplDocs {
+    /// Docs of the target itself
+    pub docs: Option<String>,
+    /// Function docs keyed the way WIT names the function (`[method]r.m`, ...)
+    pub funcs: BTreeMap<String, String>,
+    /// Docs of the target's resources
+    pub resources: BTreeMap<String, String>,
+}
+
+/// All docs harvested from a project.
+#[derive(Default)]
+pub struct GuestDocs {
+    pub impls: BTreeMap<Target, ImplDocs>,
+}
+
+/// Scan the sources of the `lang` project in `project`.
+pub fn harvest(lang: Lang, project: &Path) -> Result<GuestDocs> {
+    let adapter = lang.adapter();
+    let mut docs = GuestDocs::default();
+    for file in adapter.source_files(project)? {
+        let text = fs::read_to_string(&file).with_context(|| format!("reading {:?}", file))?;
+        adapter.scan(&file, &text, &mut docs);
+    }
+    Ok(docs)
+}
+
+/// Hash input for the cache: every source the `lang` adapter reads.
+pub fn source_files(lang: Lang, project: &Path) -> Result<Vec<PathBuf>> {
+    lang.adapter().source_files(project)
+}
+
+/// The files below `dir` with one of `extensions`, in path order. Hidden
+/// directories and those holding dependencies or build output are skipped.
+pub fn files_with_extensions(dir: &Path, extensions: &[&str], files: &mut Vec<PathBuf>) -> Result<()> {
+    const SKIPPED: &[&str] = &["node_modules", "target", "__pycache__"];
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("reading {:?}", dir))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    for entry in entries {
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if entry.is_dir() {
+            if !name.starts_with('.') && !SKIPPED.contains(&name.as_ref()) {
+                files_with_extensions(&entry, extensions, files)?;
+            }
+        } else if entry.extension().is_some_and(|e| extensions.iter().any(|ext| e == *ext)) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// `my_func` / `r#type` => `my-func` / `type`
+pub fn kebab(ident: &str) -> String {
+    ident.trim_start_matches("r#").replace('_', "-")
+}
+
+/// `MyResource` / `fetchItems` => `my-resource` / `fetch-items`
+pub fn camel_to_kebab(ident: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('-');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+impl GuestDocs {
+    /// Fill in docs missing from the WIT with the harvested ones. World-level
+    /// items are matched in `worlds`; package interfaces anywhere in
+    /// `resolve`. Returns how many docs were filled in.
+    pub fn apply(&self, resolve: &mut Resolve, worlds: &[WorldId]) -> usize {
+        let mut filled = 0;
+        for (target, impl_docs) in &self.impls {
+            match target {
+                Target::World => {
+                    for &world_id in worlds {
+                        let world = &mut resolve.worlds[world_id];
+                        filled += fill(&mut world.docs, impl_docs.docs.as_deref());
+                        for (key, item) in world.exports.iter_mut() {
+                            if let (WorldKey::Name(name), WorldItem::Function(func)) = (key, item) {
+                                filled += fill_function(func, impl_docs.funcs.get(name));
+                            }
+                        }
+                    }
+                }
+                Target::NamedInterface(name) => {
+                    let ids: Vec<InterfaceId> = worlds
+                        .iter()
+                        .filter_map(|w| match resolve.worlds[*w].exports.get(&WorldKey::Name(name.clone())) {
+                            Some(WorldItem::Interface { id, .. }) => Some(*id),
+                            _ => None,
+                        })
+                        .collect();
+                    for id in ids {
+                        filled += fill_interface(resolve, id, impl_docs);
+                    }
+                }
+                Target::PackageInterface { namespace, package, interface } => {
+                    let ids: Vec<InterfaceId> = resolve
+                        .packages
+                        .iter()
+                        .filter(|(_, pkg)| pkg.name.namespace == *namespace && pkg.name.name == *package)
+                        .filter_map(|(_, pkg)| pkg.interfaces.get(interface).copied())
+                        .collect();
+                    for id in ids {
+                        filled += fill_interface(resolve, id, impl_docs);
+                    }
+                }
+                Target::ExportedInterface(name) => {
+                    let ids: Vec<InterfaceId> = worlds
+                        .iter()
+                        .flat_map(|w| &resolve.worlds[*w].exports)
+                        .filter_map(|(key, item)| match (key, item) {
+                            (WorldKey::Name(export), WorldItem::Interface { id, .. }) if export == name => Some(*id),
+                            (WorldKey::Interface(_), WorldItem::Interface { id, .. })
+                                if resolve.interfaces[*id].name.as_ref() == Some(name) =>
+                            {
+                                Some(*id)
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    for id in ids {
+                        filled += fill_interface(resolve, id, impl_docs);
+                    }
+                }
+            }
+        }
+        debug!(filled, "applied docs from guest sources");
+        filled
+    }
+}
+
+fn fill_interface(resolve: &mut Resolve, id: InterfaceId, impl_docs: &ImplDocs) -> usize {
+    let mut filled = 0;
+    let iface = &mut resolve.interfaces[id];
+    filled += fill(&mut iface.docs, impl_docs.docs.as_deref());
+    for (name, func) in iface.functions.iter_mut() {
+        filled += fill_function(func, impl_docs.funcs.get(name));
+    }
+    let resources: Vec<_> = impl_docs
+        .resources
+        .iter()
+        .filter_map(|(name, docs)| Some((*iface.types.get(name)?, docs)))
+        .collect();
+    for (type_id, docs) in resources {
+        filled += fill(&mut resolve.types[type_id].docs, Some(docs));
+    }
+    filled
+}
+
+fn fill_function(func: &mut Function, docs: Option<&String>) -> usize {
+    fill(&mut func.docs, docs.map(|d| d.as_str()))
+}
+
+/// Set `docs` to `new` unless it already has content.
+fn fill(docs: &mut Docs, new: Option<&str>) -> usize {
+    match new {
+        Some(new) if docs.contents.as_deref().is_none_or(|d| d.trim().is_empty()) => {
+            docs.contents = Some(new.to_string());
+            1
+        }
+        _ => 0,
+    }
+}
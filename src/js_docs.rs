@@ -0,0 +1,179 @@
+//! Harvest JSDoc comments from the JavaScript or TypeScript sources of a
+//! component built with jco.
+//!
+//! jco maps the world's exported functions to functions exported from the
+//! module (`export function fetchItems`), and each exported interface to an
+//! exported object named after it in camelCase (`export const
+//! incomingHandler = { handle() { ... } }`). The `/** ... */` comments on
+//! those are recorded against the WIT items; block tags (`@param`, ...) are
+//! left out. Sources are scanned line by line rather than parsed, so only
+//! these plain forms are recognized.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::guest_docs::{Adapter, GuestDocs, Target, camel_to_kebab, files_with_extensions};
+
+/// The JavaScript/TypeScript adapter: every source file in the project,
+/// except declaration files and dependencies.
+pub struct Js;
+
+impl Adapter for Js {
+    fn source_files(&self, project: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        files_with_extensions(project, &["js", "mjs", "ts", "mts"], &mut files)?;
+        files.retain(|file| !file.to_string_lossy().ends_with(".d.ts"));
+        Ok(files)
+    }
+
+    fn scan(&self, _file: &Path, text: &str, docs: &mut GuestDocs) {
+        let mut depth = 0usize;
+        let mut comment: Option<Vec<&str>> = None;
+        let mut pending = None;
+        // The exported interface object being scanned.
+        let mut object: Option<Target> = None;
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if let Some(lines) = &mut comment {
+                match trimmed.split_once("*/") {
+                    Some((last, _)) => {
+                        lines.push(last);
+                        pending = jsdoc_text(lines);
+                        comment = None;
+                    }
+                    None => lines.push(trimmed),
+                }
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix("/**") {
+                match rest.split_once("*/") {
+                    Some((body, _)) => pending = jsdoc_text(&[body]),
+                    None => comment = Some(vec![rest]),
+                }
+                continue;
+            }
+            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('@') {
+                continue;
+            }
+
+            let doc = pending.take();
+            match (depth, &object) {
+                (0, _) => match exported(trimmed) {
+                    Some(Export::Function(name)) => {
+                        if let Some(doc) = doc {
+                            docs.impls.entry(Target::World).or_default().funcs.insert(camel_to_kebab(name), doc);
+                        }
+                    }
+                    Some(Export::Object(name)) => {
+                        let target = Target::ExportedInterface(camel_to_kebab(name));
+                        if let Some(doc) = doc {
+                            docs.impls.entry(target.clone()).or_default().docs = Some(doc);
+                        }
+                        object = Some(target);
+                    }
+                    None => {}
+                },
+                (1, Some(target)) => {
+                    if let (Some(name), Some(doc)) = (method(trimmed), doc) {
+                        docs.impls.entry(target.clone()).or_default().funcs.insert(camel_to_kebab(name), doc);
+                    }
+                }
+                _ => {}
+            }
+
+            depth = braces(line, depth);
+            if depth == 0 {
+                object = None;
+            }
+        }
+    }
+}
+
+/// Something a module exports at the top level.
+enum Export<'a> {
+    Function(&'a str),
+    Object(&'a str),
+}
+
+/// `export [async] function name(`, `export const name = (...) =>` and
+/// `export const name = {`.
+fn exported(line: &str) -> Option<Export<'_>> {
+    let rest = line.strip_prefix("export ")?.trim_start();
+    let rest = rest.strip_prefix("async ").map_or(rest, str::trim_start);
+    if let Some(rest) = rest.strip_prefix("function") {
+        let rest = rest.trim_start().trim_start_matches('*').trim_start();
+        return Some(Export::Function(ident(rest)?));
+    }
+    let rest = ["const ", "let ", "var "].iter().find_map(|kw| rest.strip_prefix(kw))?.trim_start();
+    let name = ident(rest)?;
+    let value = rest[name.len()..].split_once('=')?.1.trim_start();
+    if value.starts_with('{') {
+        Some(Export::Object(name))
+    } else if is_function_value(value) {
+        Some(Export::Function(name))
+    } else {
+        None
+    }
+}
+
+/// A method of an object literal: `name(`, `async name(`, `*name(`, or
+/// `name: function`/`name: (...) =>`.
+fn method(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("async ").map_or(line, str::trim_start);
+    let rest = rest.trim_start_matches('*').trim_start();
+    let name = ident(rest)?;
+    let after = rest[name.len()..].trim_start();
+    if after.starts_with('(') || after.starts_with('<') {
+        return Some(name);
+    }
+    let value = after.strip_prefix(':')?.trim_start();
+    is_function_value(value).then_some(name)
+}
+
+fn is_function_value(value: &str) -> bool {
+    value.starts_with("function") || value.starts_with("async") || value.starts_with('(')
+}
+
+/// The identifier `text` starts with.
+fn ident(text: &str) -> Option<&str> {
+    let end = text.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(text.len());
+    (end > 0).then(|| &text[..end])
+}
+
+/// The brace depth after `line`, starting from `depth`. Braces in strings
+/// and after `//` don't count.
+fn braces(line: &str, mut depth: usize) -> usize {
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '/') if chars.peek() == Some(&'/') => break,
+            (None, '{') => depth += 1,
+            (None, '}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// The description of a JSDoc comment: its lines without the leading `*`,
+/// up to the first block tag.
+fn jsdoc_text(lines: &[&str]) -> Option<String> {
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').map_or(line, |l| l.strip_prefix(' ').unwrap_or(l))
+        })
+        .take_while(|line| !line.starts_with('@'))
+        .collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
@@ -0,0 +1,1196 @@
+//! Reading the docs sections that `wit-docs-inject` writes, shared by the
+//! `wit-docs-*` binaries.
+//!
+//! Functions fail with an [`Error`], whose variants tell the causes apart
+//! (an unreadable file, a malformed component, a missing section, ...).
+
+#[cfg(feature = "cli")]
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use memmap2::Mmap;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "cli")]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use tracing::debug;
+use wasmparser::{Chunk, ComponentName, KnownCustom, Parser as WasmParser, Payload};
+use wit_component::{DecodedWasm, WitPrinter};
+use wit_parser::{
+    Function, FunctionKind, PackageId, PackageMetadata, Resolve, Type, TypeDefKind, TypeId, TypeOwner,
+    UnresolvedPackageGroup, WorldId, WorldItem, WorldKey,
+};
+
+/// Companion custom section carrying data that the `package-docs` schema has
+/// no room for (it rejects unknown fields).
+pub const EXT_SECTION_NAME: &str = "package-docs-ext";
+
+/// Custom section holding the printed WIT package, written with `--embed-wit`.
+pub const WIT_SECTION_NAME: &str = "package-docs-wit";
+
+/// Custom section on a composed component that gathers the docs of its
+/// constituent components, keyed by the package each was taken from.
+pub const COMPOSED_SECTION_NAME: &str = "package-docs-composed";
+
+/// Version byte that starts a `package-docs` payload in the current format.
+pub const PACKAGE_DOCS_VERSION: u8 = 1;
+
+/// Why reading or writing docs failed, for callers that handle some causes
+/// differently from others.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The component isn't valid WebAssembly.
+    #[error("Failed to parse WebAssembly")]
+    ComponentParse(#[from] wasmparser::BinaryReaderError),
+
+    #[error("Failed to parse WebAssembly: the component is truncated")]
+    ComponentTruncated,
+
+    /// The component's type information doesn't decode to WIT.
+    #[error("Failed to decode the component's WIT")]
+    WitDecode(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// WIT text doesn't parse or resolve.
+    #[error("Failed to parse {0}")]
+    WitParse(&'static str, #[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// A docs section isn't valid UTF-8 or JSON, or doesn't match its schema.
+    #[error("Failed to parse {section} JSON")]
+    DocsDecode {
+        section: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("{0} is not UTF-8")]
+    NotUtf8(&'static str, #[source] std::str::Utf8Error),
+
+    /// Docs that toolchains would reject.
+    #[error("Failed to encode package-docs")]
+    DocsEncode(#[source] serde_json::Error),
+
+    #[error("No {0} found in component")]
+    SectionMissing(&'static str),
+
+    /// The docs section isn't the one written at injection time: it was
+    /// truncated or altered on the way.
+    #[error("{section} is corrupt: its digest is {actual}, but {expected} was recorded at injection")]
+    DigestMismatch { section: &'static str, expected: String, actual: String },
+
+    #[error("World `{world}` is not part of package `{package}`")]
+    WorldNotInPackage { world: String, package: String },
+
+    #[error("No documentation for world `{world}` (documented worlds: {})", documented_list(.documented))]
+    UndocumentedWorld { world: String, documented: Vec<String> },
+}
+
+fn documented_list(documented: &[String]) -> String {
+    if documented.is_empty() { "none".to_string() } else { documented.join(", ") }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Set by `--quiet`, which also hides progress bars.
+#[cfg(feature = "cli")]
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Logging flags shared by the binaries.
+#[cfg(feature = "cli")]
+#[derive(clap::Args, Debug)]
+pub struct Verbosity {
+    /// Log more: `-v` for debug output and the time spent in each phase, `-vv` for everything
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Only log errors, and show no progress
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[cfg(feature = "cli")]
+impl Verbosity {
+    /// Install the stderr logger. `RUST_LOG`, when set, overrides the flags.
+    pub fn init(&self) {
+        init_logging(self.verbose, self.quiet);
+    }
+}
+
+/// Flags for packagers, shared by the binaries: print a shell completion
+/// script, or write a man page, instead of running the tool.
+#[cfg(feature = "cli")]
+#[derive(clap::Args, Debug, Clone)]
+pub struct Packaging {
+    /// Print a completion script for SHELL and exit
+    #[arg(long, value_name = "SHELL", exclusive = true, hide = true)]
+    pub completions: Option<clap_complete::Shell>,
+
+    /// Write the man page to DIR and exit
+    #[arg(long, value_name = "DIR", exclusive = true, hide = true)]
+    pub manpages: Option<PathBuf>,
+}
+
+#[cfg(feature = "cli")]
+impl Packaging {
+    /// Generate what was asked for, for the binary `name` whose arguments
+    /// are `C`. Returns whether anything was, in which case the binary has
+    /// nothing else to do.
+    pub fn handle<C: clap::CommandFactory>(&self, name: &'static str) -> Result<bool> {
+        let mut command = C::command().name(name).bin_name(name);
+        if let Some(shell) = self.completions {
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+            return Ok(true);
+        }
+        if let Some(dir) = &self.manpages {
+            fs::create_dir_all(dir)?;
+            let mut file = AtomicFile::create(&dir.join(format!("{}.1", name)))?;
+            clap_mangen::Man::new(command).render(file.file())?;
+            file.commit()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// Hide all progress bars.
+#[cfg(feature = "cli")]
+pub fn hide_progress() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// Install the stderr logger for `-v` repeated `verbose` times or `-q`.
+/// `RUST_LOG`, when set, overrides the flags.
+#[cfg(feature = "cli")]
+pub fn init_logging(verbose: u8, quiet: bool) {
+    use std::io::IsTerminal;
+    use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+
+    if quiet {
+        hide_progress();
+    }
+    // `-v` is about this tool's phases; dependencies only log at `-vv`.
+    let level = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "info",
+        (false, 1) => "info,wit_docs=debug",
+        (false, _) => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    // Phase timings are reported as spans close.
+    let span_events = if verbose > 0 { FmtSpan::CLOSE } else { FmtSpan::NONE };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(span_events)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .with_timer(())
+        .with_ansi(std::io::stderr().is_terminal())
+        .init();
+}
+
+/// Map the file at `path` into memory instead of reading it, so that finding
+/// or appending a custom section doesn't copy a large component onto the heap.
+pub fn map_file(path: &Path) -> Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is only ever read. As with any mapped file, another
+    // process truncating it meanwhile would fault; the tools themselves drop
+    // the mapping before writing to the same path.
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+/// A file written next to its target and renamed over it once complete, so
+/// that a crash mid-write never leaves the target truncated. The temporary
+/// file is removed if the write is abandoned.
+pub struct AtomicFile {
+    file: Option<File>,
+    temp: PathBuf,
+    target: PathBuf,
+}
+
+impl AtomicFile {
+    /// Start writing a replacement for `target`.
+    pub fn create(target: &Path) -> std::io::Result<Self> {
+        let dir = match target.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+        let file = File::create(&temp)?;
+        // The replacement keeps the original's permissions.
+        if let Ok(metadata) = fs::metadata(target) {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        Ok(AtomicFile { file: Some(file), temp, target: target.to_path_buf() })
+    }
+
+    pub fn file(&mut self) -> &mut File {
+        self.file.as_mut().expect("file is open until committed")
+    }
+
+    /// The temporary file being written, to read back what was written
+    /// before committing it.
+    pub fn path(&self) -> &Path {
+        &self.temp
+    }
+
+    /// Flush the new contents to disk and move them into place.
+    pub fn commit(mut self) -> std::io::Result<()> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        fs::rename(&self.temp, &self.target).inspect_err(|_| {
+            let _ = fs::remove_file(&self.temp);
+        })
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Still open: the write didn't complete.
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
+
+/// `component.wasm` => `component.wasm.bak`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Components at least this large get a progress bar while being scanned or
+/// written.
+#[cfg(feature = "cli")]
+const LARGE_COMPONENT: usize = 32 << 20;
+
+/// A progress bar on stderr for work over `len` bytes of a component. It's
+/// hidden for small components, and (by indicatif) when stderr isn't a terminal.
+#[cfg(feature = "cli")]
+pub fn byte_progress(len: usize, message: &'static str) -> ProgressBar {
+    if len < LARGE_COMPONENT || QUIET.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{msg} [{bar:40}] {bytes}/{total_bytes} ({eta})")
+        .expect("valid progress template")
+        .progress_chars("=> ");
+    ProgressBar::new(len as u64)
+        .with_style(style)
+        .with_message(message)
+        .with_finish(ProgressFinish::AndClear)
+}
+
+/// A spinner on stderr for a step of unknown length, cleared when dropped.
+#[cfg(feature = "cli")]
+pub fn spinner(message: &'static str) -> ProgressBar {
+    if QUIET.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner()
+        .with_message(message)
+        .with_finish(ProgressFinish::AndClear);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// One of the docs custom sections as it appears in the binary.
+#[derive(Clone, Copy)]
+pub struct DocsSection<'a> {
+    /// Offset of the section's data within the file
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+/// The first custom section called `name` of the component itself (not of
+/// the components or modules nested in it), if any.
+pub fn find_custom_section<'a>(wasm_bytes: &'a [u8], name: &str) -> Result<Option<DocsSection<'a>>> {
+    Ok(scan_custom_sections(wasm_bytes, name, true)?.into_iter().next())
+}
+
+/// Every custom section called `name` of the component itself, in the order
+/// they appear. Components injected more than once, or carrying docs for
+/// several packages, have more than one.
+pub fn find_custom_sections<'a>(wasm_bytes: &'a [u8], name: &str) -> Result<Vec<DocsSection<'a>>> {
+    scan_custom_sections(wasm_bytes, name, false)
+}
+
+fn scan_custom_sections<'a>(wasm_bytes: &'a [u8], name: &str, first_only: bool) -> Result<Vec<DocsSection<'a>>> {
+    let mut parser = WasmParser::new(0);
+    // The library alone shows no progress.
+    #[cfg(feature = "cli")]
+    let progress = byte_progress(wasm_bytes.len(), "Scanning component");
+    let mut offset = 0;
+    let mut found = Vec::new();
+
+    while offset < wasm_bytes.len() {
+        let (payload, consumed) = next_payload(&mut parser, wasm_bytes, offset)?;
+        offset += consumed;
+        match payload {
+            // Nested modules and components, code and all, are stepped over
+            // without being parsed: their sections belong to them.
+            Payload::ModuleSection { unchecked_range, .. } | Payload::ComponentSection { unchecked_range, .. } => {
+                offset += unchecked_range.len();
+            }
+            Payload::CustomSection(reader) if reader.name() == name => {
+                found.push(DocsSection {
+                    offset: reader.data_offset(),
+                    data: reader.data(),
+                });
+                if first_only {
+                    break;
+                }
+            }
+            Payload::End(_) => break,
+            _ => {}
+        }
+        #[cfg(feature = "cli")]
+        progress.set_position(offset as u64);
+    }
+
+    Ok(found)
+}
+
+/// The next payload of `parser`, which has consumed `wasm_bytes` up to
+/// `offset`, and the number of bytes it takes up.
+fn next_payload<'a>(parser: &mut WasmParser, wasm_bytes: &'a [u8], offset: usize) -> Result<(Payload<'a>, usize)> {
+    match parser.parse(&wasm_bytes[offset..], true)? {
+        Chunk::Parsed { payload, consumed } => Ok((payload, consumed)),
+        Chunk::NeedMoreData(_) => Err(Error::ComponentTruncated),
+    }
+}
+
+/// The docs sections of a component nested in a composed one.
+pub struct NestedDocs<'a> {
+    pub package_docs: DocsSection<'a>,
+    pub ext: Option<DocsSection<'a>>,
+}
+
+/// The docs sections of every component nested (at any depth) in a
+/// composed component, in the order the components appear.
+pub fn find_nested_docs(wasm_bytes: &[u8]) -> Result<Vec<NestedDocs<'_>>> {
+    let nested = nested_components(wasm_bytes, PackageMetadata::SECTION_NAME)?;
+    let with_docs = nested.into_iter().filter_map(|c| Some(NestedDocs { package_docs: c.package_docs?, ext: c.ext }));
+    Ok(with_docs.collect())
+}
+
+/// A component nested in another, e.g. by composition, with its docs
+/// sections.
+pub struct NestedComponent<'a> {
+    /// Position among all nested components, in the order they appear, from 0
+    pub index: usize,
+    /// 1 for the components of the outermost one, 2 for theirs, ...
+    pub depth: usize,
+    /// The name it gives itself in its `component-name` section
+    pub name: Option<&'a str>,
+    /// The whole nested component
+    pub bytes: &'a [u8],
+    pub package_docs: Option<DocsSection<'a>>,
+    pub ext: Option<DocsSection<'a>>,
+    pub wit: Option<DocsSection<'a>>,
+}
+
+/// Every component nested (at any depth) in `wasm_bytes`, in the order they
+/// appear, with the docs section `section_name` and its companions.
+pub fn nested_components<'a>(wasm_bytes: &'a [u8], section_name: &str) -> Result<Vec<NestedComponent<'a>>> {
+    // One entry per nested component being parsed: its parser, and what's
+    // known of it so far. Core modules carry no docs and are stepped over.
+    let mut stack: Vec<(WasmParser, NestedComponent<'a>)> = Vec::new();
+    let mut parser = WasmParser::new(0);
+    let mut offset = 0;
+    let mut found = Vec::new();
+    let mut count = 0;
+
+    while offset < wasm_bytes.len() {
+        let current = stack.last_mut().map_or(&mut parser, |(parser, _)| parser);
+        let (payload, consumed) = next_payload(current, wasm_bytes, offset)?;
+        offset += consumed;
+        match payload {
+            Payload::ModuleSection { unchecked_range, .. } => offset += unchecked_range.len(),
+            Payload::ComponentSection { parser: nested, unchecked_range } => {
+                let component = NestedComponent {
+                    index: count,
+                    depth: stack.len() + 1,
+                    name: None,
+                    bytes: &wasm_bytes[unchecked_range],
+                    package_docs: None,
+                    ext: None,
+                    wit: None,
+                };
+                count += 1;
+                stack.push((nested, component));
+            }
+            Payload::End(_) => match stack.pop() {
+                Some((_, component)) => found.push(component),
+                // The end of the outermost component.
+                None => break,
+            },
+            Payload::CustomSection(reader) => {
+                // The outermost component's own sections aren't of interest.
+                let Some((_, component)) = stack.last_mut() else {
+                    continue;
+                };
+                let section = DocsSection {
+                    offset: reader.data_offset(),
+                    data: reader.data(),
+                };
+                match (reader.name(), reader.as_known()) {
+                    (name, _) if name == section_name => component.package_docs = Some(section),
+                    (EXT_SECTION_NAME, _) => component.ext = Some(section),
+                    (WIT_SECTION_NAME, _) => component.wit = Some(section),
+                    (_, KnownCustom::ComponentName(names)) => {
+                        component.name = names.into_iter().find_map(|name| match name.ok()? {
+                            ComponentName::Component { name, .. } => Some(name),
+                            _ => None,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Components are complete in the order they end; inner ones first.
+    found.sort_by_key(|c| c.index);
+    Ok(found)
+}
+
+/// The digest of a `package-docs` payload (version byte included), as
+/// recorded in `package-docs-ext`: `sha256:<hex>`.
+pub fn docs_digest(data: &[u8]) -> String {
+    let hash = Sha256::digest(data);
+    let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256:{}", hex)
+}
+
+/// Check a `package-docs` payload against the digest its `package-docs-ext`
+/// records, failing with [`Error::DigestMismatch`] when they differ. Returns
+/// whether there was a digest to check: docs written by other tools, or by
+/// versions of this one before digests were recorded, have none.
+pub fn verify_digest(data: &[u8], ext: &Value) -> Result<bool> {
+    let Some(expected) = ext.get("digest").and_then(|d| d.as_str()) else {
+        return Ok(false);
+    };
+    let actual = docs_digest(data);
+    if actual != expected {
+        let section = PackageMetadata::SECTION_NAME;
+        return Err(Error::DigestMismatch { section, expected: expected.to_string(), actual });
+    }
+    Ok(true)
+}
+
+/// Parse a `package-docs` payload (version byte + JSON) into JSON, or `None`
+/// for an empty payload. Legacy layouts are normalized to the current schema
+/// (see [`normalize_docs`]).
+pub fn decode_package_docs(data: &[u8]) -> Result<Option<Value>> {
+    // Skip the first byte (version) and parse the JSON
+    if data.len() > 1 {
+        let json_data = &data[1..];
+        let mut docs: Value = serde_json::from_slice(json_data)
+            .map_err(|source| Error::DocsDecode { section: PackageMetadata::SECTION_NAME, source })?;
+        for change in normalize_docs(&mut docs) {
+            debug!("legacy package-docs: {}", change);
+        }
+        return Ok(Some(docs));
+    }
+    Ok(None)
+}
+
+/// Encode `docs` as a `package-docs` payload in the current format, checked
+/// against the schema toolchains read it with.
+pub fn encode_package_docs(docs: &Value) -> Result<Vec<u8>> {
+    let mut data = vec![PACKAGE_DOCS_VERSION];
+    serde_json::to_writer(&mut data, docs).map_err(Error::DocsEncode)?;
+    // What `PackageMetadata::decode` reads after the version byte
+    serde_json::from_slice::<PackageMetadata>(&data[1..]).map_err(Error::DocsEncode)?;
+    Ok(data)
+}
+
+/// The docs of a component and its `package-docs-ext` (`null` without
+/// one), failing with [`Error::SectionMissing`] when it has no
+/// `package-docs` section.
+pub fn read_docs(wasm_bytes: &[u8]) -> Result<(Value, Value)> {
+    let section = find_custom_section(wasm_bytes, PackageMetadata::SECTION_NAME)?
+        .ok_or(Error::SectionMissing(PackageMetadata::SECTION_NAME))?;
+    let docs = decode_package_docs(section.data)?.unwrap_or(Value::Null);
+    let ext = match find_custom_section(wasm_bytes, EXT_SECTION_NAME)? {
+        Some(ext) => serde_json::from_slice(ext.data)
+            .map_err(|source| Error::DocsDecode { section: EXT_SECTION_NAME, source })?,
+        None => Value::Null,
+    };
+    Ok((docs, ext))
+}
+
+/// World keys used by older producers (including early versions of this
+/// tool) for what the current schema calls `funcs` and `interfaces`.
+const LEGACY_WORLD_KEYS: &[(&str, &str)] = &[
+    ("functions", "funcs"),
+    ("func_imports", "funcs"),
+    ("interface_imports", "interfaces"),
+];
+
+/// Rewrite the legacy layouts in a `package-docs` payload to the current
+/// schema, returning a description of each change:
+///
+/// - world function maps under a legacy key (`functions`, `func_imports`)
+///   move to `funcs`, and `interface_imports` to `interfaces`; entries
+///   already under the current key win
+/// - functions documented with a bare string (the version 0 format) become
+///   `{"docs": ...}` objects
+pub fn normalize_docs(docs: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    if let Some(worlds) = docs.get_mut("worlds").and_then(|w| w.as_object_mut()) {
+        for (name, world) in worlds.iter_mut() {
+            let Some(world) = world.as_object_mut() else {
+                continue;
+            };
+            for (legacy, current) in LEGACY_WORLD_KEYS {
+                let Some(Value::Object(entries)) = world.remove(*legacy) else {
+                    continue;
+                };
+                changes.push(format!("world `{}`: moved `{}` to `{}`", name, legacy, current));
+                let target = world.entry(*current).or_insert_with(|| Value::Object(Default::default()));
+                if let Some(target) = target.as_object_mut() {
+                    for (key, value) in entries {
+                        target.entry(key).or_insert(value);
+                    }
+                }
+            }
+            for map in ["funcs", "func_exports"] {
+                normalize_functions(world.get_mut(map), &format!("world `{}`", name), &mut changes);
+            }
+            for map in ["interfaces", "interface_exports"] {
+                for (iface, data) in world.get_mut(map).and_then(|i| i.as_object_mut()).into_iter().flatten() {
+                    let container = format!("world `{}` interface `{}`", name, iface);
+                    normalize_functions(data.get_mut("funcs"), &container, &mut changes);
+                }
+            }
+        }
+    }
+    for (name, iface) in docs.get_mut("interfaces").and_then(|i| i.as_object_mut()).into_iter().flatten() {
+        normalize_functions(iface.get_mut("funcs"), &format!("interface `{}`", name), &mut changes);
+    }
+    changes
+}
+
+/// Turn the bare-string function docs in `funcs` into objects.
+fn normalize_functions(funcs: Option<&mut Value>, container: &str, changes: &mut Vec<String>) {
+    let mut count = 0;
+    for (_, func) in funcs.and_then(|f| f.as_object_mut()).into_iter().flatten() {
+        match func {
+            Value::String(docs) => {
+                *func = serde_json::json!({ "docs": std::mem::take(docs) });
+                count += 1;
+            }
+            Value::Null => {
+                *func = serde_json::json!({});
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+    if count > 0 {
+        changes.push(format!("{}: {} function(s) documented with a bare string", container, count));
+    }
+}
+
+/// The WIT of a component: its embedded WIT package (see `--embed-wit`), or
+/// the WIT decoded from its type information along with the component's
+/// world. Binary WIT packages decode to the whole package and no world.
+pub fn component_wit(wasm_bytes: &[u8]) -> Result<(Resolve, Option<WorldId>)> {
+    if let Some(wit) = find_custom_section(wasm_bytes, WIT_SECTION_NAME)? {
+        let text = std::str::from_utf8(wit.data).map_err(|err| Error::NotUtf8(WIT_SECTION_NAME, err))?;
+        let mut resolve = Resolve::default();
+        UnresolvedPackageGroup::parse("package-docs-wit.wit", text)
+            .and_then(|group| resolve.push_group(group))
+            .map_err(|err| Error::WitParse(WIT_SECTION_NAME, err.into()))?;
+        return Ok((resolve, None));
+    }
+    match wit_component::decode(wasm_bytes).map_err(|err| Error::WitDecode(err.into()))? {
+        DecodedWasm::WitPackage(resolve, _) => Ok((resolve, None)),
+        DecodedWasm::Component(resolve, world) => Ok((resolve, Some(world))),
+    }
+}
+
+/// Parse the WIT package at `path`, either a package directory or a single
+/// `.wit` file. A `deps/` directory next to a single file is loaded first, the
+/// same way `Resolve::push_dir` treats one inside a package directory.
+pub fn push_wit_path(resolve: &mut Resolve, path: &Path) -> Result<PackageId> {
+    if path.is_dir() {
+        return Ok(resolve.push_dir(path).map_err(|err| Error::WitParse("WIT sources", err.into()))?.0);
+    }
+    let deps = path.parent().unwrap_or_else(|| Path::new(".")).join("deps");
+    if deps.is_dir() {
+        push_deps(resolve, &deps)?;
+    }
+    resolve.push_file(path).map_err(|err| Error::WitParse("WIT sources", err.into()))
+}
+
+/// Load every package in a `deps/` directory, pushing each one only after the
+/// packages it depends on.
+fn push_deps(resolve: &mut Resolve, deps: &Path) -> Result<()> {
+    let failed = |err: anyhow::Error| {
+        Error::WitParse("WIT sources", err.context(format!("parsing dependency directory {:?}", deps)).into())
+    };
+    let mut entries = fs::read_dir(deps)
+        .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+        .map_err(|err| failed(err.into()))?;
+    entries.sort_by_key(|e| e.file_name());
+
+    let mut pending = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            pending.push(UnresolvedPackageGroup::parse_dir(&path).map_err(failed)?);
+        } else {
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("wit") => pending.push(UnresolvedPackageGroup::parse_file(&path).map_err(failed)?),
+                // Binary packages are self-contained and carry their own deps
+                Some("wasm") => {
+                    resolve.push_file(&path).map_err(failed)?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|group| {
+            group.main.foreign_deps.keys().all(|dep| {
+                resolve.package_names.contains_key(dep) || group.nested.iter().any(|nested| &nested.name == dep)
+            })
+        });
+        // Nothing is ready: push anyway so wit-parser reports what's missing.
+        let group = pending.remove(ready.unwrap_or(0));
+        resolve.push_group(group).map_err(failed)?;
+    }
+    Ok(())
+}
+
+/// The worlds, interfaces, functions and types of `package` (`ns:pkg@1.0.0`)
+/// in WIT read with [`component_wit`], as `(path, kind)` pairs. Types
+/// brought in by `use` count only where they are defined. The
+/// component's anonymous world stands for the documented world
+/// `world_name`; without one it is left out.
+pub fn documentable_items(
+    resolve: &Resolve,
+    component_world: Option<WorldId>,
+    package: &str,
+    world_name: Option<&str>,
+) -> Vec<(String, &'static str)> {
+    let mut items = Vec::new();
+    let in_package =
+        |pkg: Option<PackageId>| pkg.is_some_and(|pkg| resolve.packages[pkg].name.to_string() == package);
+    for (_, iface) in resolve.interfaces.iter() {
+        let Some(name) = &iface.name else {
+            continue;
+        };
+        if !in_package(iface.package) {
+            continue;
+        }
+        let path = qualify(Some(package), name);
+        items.push((path.clone(), "interface"));
+        for func in iface.functions.keys() {
+            items.push((format!("{}#{}", path, func), "function"));
+        }
+        for (ty, &id) in &iface.types {
+            if !is_use(resolve, id) {
+                items.push((format!("{}#{}", path, ty), "type"));
+            }
+        }
+    }
+    for (id, world) in resolve.worlds.iter() {
+        let path = match component_world {
+            Some(component_world) if component_world == id => match world_name {
+                Some(name) => qualify(Some(package), name),
+                None => continue,
+            },
+            Some(_) => continue,
+            None if in_package(world.package) => qualify(Some(package), &world.name),
+            None => continue,
+        };
+        items.push((path.clone(), "world"));
+        for (key, item) in world.imports.iter().chain(world.exports.iter()) {
+            let WorldKey::Name(name) = key else {
+                continue;
+            };
+            match item {
+                WorldItem::Function(_) => items.push((format!("{}#{}", path, name), "function")),
+                WorldItem::Type(id) if is_use(resolve, *id) => {}
+                WorldItem::Type(_) => items.push((format!("{}#{}", path, name), "type")),
+                WorldItem::Interface { id, .. } => {
+                    items.push((format!("{}#{}", path, name), "interface"));
+                    for func in resolve.interfaces[*id].functions.keys() {
+                        items.push((format!("{}#{}.{}", path, name, func), "function"));
+                    }
+                }
+            }
+        }
+    }
+    items
+}
+
+/// How many of a package's documentable items have docs.
+pub struct Coverage {
+    pub documented: usize,
+    pub total: usize,
+}
+
+impl Coverage {
+    /// The share of items with docs, in percent to one decimal (100 for a
+    /// package with nothing to document).
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            0 => 100.0,
+            _ => (self.documented as f64 * 1000.0 / self.total as f64).round() / 10.0,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({ "documented": self.documented, "total": self.total, "percent": self.percent() })
+    }
+}
+
+/// The docs coverage of `package` in a component: how many of the
+/// [`documentable_items`] of its WIT `docs` documents, counted like
+/// `wit-docs-lint`'s `undocumented` rule.
+pub fn coverage(wasm_bytes: &[u8], docs: &Value, ext: &Value, package: &str) -> Result<Coverage> {
+    let (resolve, component_world) = component_wit(wasm_bytes)?;
+    let world_name = match ext.get("world").and_then(|w| w.as_str()) {
+        Some(world) => Some(world.to_string()),
+        None => match docs.get("worlds").and_then(|w| w.as_object()) {
+            Some(worlds) if worlds.len() == 1 => worlds.keys().next().cloned(),
+            _ => None,
+        },
+    };
+
+    let items = collect_items(docs, Some(package), None);
+    let documented: BTreeSet<&str> =
+        items.iter().filter(|item| item.docs.is_some()).map(|item| item.path.as_str()).collect();
+    let known = documentable_items(&resolve, component_world, package, world_name.as_deref());
+    Ok(Coverage {
+        documented: known.iter().filter(|(path, _)| documented.contains(path.as_str())).count(),
+        total: known.len(),
+    })
+}
+
+/// Whether a type was brought in by `use` from another interface: it is
+/// documented where it is defined, so its own docs are optional.
+pub fn is_use(resolve: &Resolve, id: TypeId) -> bool {
+    let ty = &resolve.types[id];
+    match ty.kind {
+        TypeDefKind::Type(Type::Id(other)) => {
+            let owner = resolve.types[other].owner;
+            owner != TypeOwner::None && owner != ty.owner
+        }
+        _ => false,
+    }
+}
+
+/// The `.wasm` files below `dir`, recursively, in path order.
+pub fn wasm_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "wasm") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The path of a package's interface or world: `ns:pkg@1.0.0` + `name` =>
+/// `ns:pkg/name@1.0.0`, or just `name` when the package is unknown.
+pub fn qualify(package: Option<&str>, name: &str) -> String {
+    match package {
+        Some(package) => match package.split_once('@') {
+            Some((package, version)) => format!("{}/{}@{}", package, name, version),
+            None => format!("{}/{}", package, name),
+        },
+        None => name.to_string(),
+    }
+}
+
+/// A world named on the command line, either bare (`fetch`) or fully
+/// qualified (`ns:pkg/fetch@1.0.0`).
+pub struct WorldSpec {
+    pub package: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl WorldSpec {
+    pub fn parse(spec: &str) -> Self {
+        let (path, version) = match spec.rsplit_once('@') {
+            Some((path, version)) => (path, Some(version.to_string())),
+            None => (spec, None),
+        };
+        match path.rsplit_once('/') {
+            Some((package, name)) => WorldSpec {
+                package: Some(package.to_string()),
+                name: name.to_string(),
+                version,
+            },
+            None => WorldSpec { package: None, name: path.to_string(), version },
+        }
+    }
+
+    /// Whether this spec can name a world of `package`, given as
+    /// `ns:pkg@version` the way `wit-docs-inject` records it.
+    pub fn matches_package(&self, package: &str) -> bool {
+        let (name, version) = match package.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (package, None),
+        };
+        self.package.as_deref().is_none_or(|p| p == name)
+            && self.version.as_deref().is_none_or(|v| Some(v) == version)
+    }
+}
+
+/// Resolve the world that docs lookups should target.
+///
+/// An explicit `--world` must name a documented world (and, when qualified,
+/// the package the docs were taken from). Otherwise the world recorded at
+/// injection time is used, if any.
+pub fn select_world(docs: &Value, ext: &Value, spec: Option<&str>) -> Result<Option<String>> {
+    let Some(spec) = spec else {
+        return Ok(ext.get("world").and_then(|w| w.as_str()).map(String::from));
+    };
+    let world = WorldSpec::parse(spec);
+    if let Some(package) = ext.get("package").and_then(|p| p.as_str())
+        && !world.matches_package(package)
+    {
+        return Err(Error::WorldNotInPackage { world: spec.to_string(), package: package.to_string() });
+    }
+
+    let worlds = docs.get("worlds").and_then(|w| w.as_object());
+    let Some((name, _)) = worlds.and_then(|w| find_key(w, &world.name)) else {
+        return Err(Error::UndocumentedWorld {
+            world: spec.to_string(),
+            documented: worlds.into_iter().flatten().map(|(name, _)| name.clone()).collect(),
+        });
+    };
+    Ok(Some(name.clone()))
+}
+
+/// The spelling of a WIT name that docs keys and printed WIT agree on.
+/// Printers escape keywords (`%type`), and docs from other tools may use
+/// `snake_case` or capitals where WIT is kebab-case.
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c != '%')
+        .map(|c| if c == '_' { '-' } else { c.to_ascii_lowercase() })
+        .collect()
+}
+
+/// The entry of a docs map named `name`: an exact match, else the one whose
+/// key has the same [`normalize_name`] spelling.
+pub fn find_key<'a>(map: &'a Map<String, Value>, name: &str) -> Option<(&'a String, &'a Value)> {
+    map.get_key_value(name).or_else(|| {
+        let name = normalize_name(name);
+        map.iter().find(|(key, _)| normalize_name(key) == name)
+    })
+}
+
+/// Whether two interface names (`ns:pkg/iface@1.0.0`) or package names
+/// (`ns:pkg@1.0.0`) are the same, with a version on one side only matching
+/// any version on the other.
+pub fn same_versioned_name(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_name(a), normalize_name(b));
+    match (a.split_once('@'), b.split_once('@')) {
+        (Some(_), Some(_)) => a == b,
+        (Some((a, _)), None) => a == b,
+        (None, Some((b, _))) => a == b,
+        (None, None) => a == b,
+    }
+}
+
+/// A documentable item, flattened out of the nested docs JSON.
+pub struct DocItem {
+    /// `ns:pkg/world#func`, `ns:pkg/iface#type.case`, ... (unqualified when
+    /// the package name wasn't recorded)
+    pub path: String,
+    pub kind: &'static str,
+    pub docs: Option<String>,
+    /// Path of the world or interface the item belongs to; `None` for
+    /// worlds and package interfaces themselves
+    pub container: Option<String>,
+    /// `import` or `export` for items of a world, `None` for definitions in
+    /// package interfaces
+    pub direction: Option<&'static str>,
+}
+
+/// Flatten every world and interface in `docs`, with their functions, types
+/// and type members, into a list of items. With `only_world` set, just that
+/// world's items are returned.
+pub fn collect_items(docs: &Value, package: Option<&str>, only_world: Option<&str>) -> Vec<DocItem> {
+    let qualify = |name: &str| qualify(package, name);
+    let mut items = Vec::new();
+
+    if only_world.is_none() {
+        for (name, interface) in docs.get("interfaces").and_then(|i| i.as_object()).into_iter().flatten() {
+            let path = qualify(name);
+            items.push(DocItem {
+                path: path.clone(),
+                kind: "interface",
+                docs: doc_text(interface),
+                container: None,
+                direction: None,
+            });
+            collect_members(interface, &format!("{}#", path), &path, None, &mut items);
+        }
+    }
+
+    for (name, world) in docs.get("worlds").and_then(|w| w.as_object()).into_iter().flatten() {
+        if only_world.is_some_and(|w| w != name) {
+            continue;
+        }
+        let path = qualify(name);
+        items.push(DocItem { path: path.clone(), kind: "world", docs: doc_text(world), container: None, direction: None });
+        collect_members(world, &format!("{}#", path), &path, Some("import"), &mut items);
+        for (func, data) in world.get("func_exports").and_then(|f| f.as_object()).into_iter().flatten() {
+            items.push(DocItem {
+                path: format!("{}#{}", path, func),
+                kind: "function",
+                docs: doc_text(data),
+                container: Some(path.clone()),
+                direction: Some("export"),
+            });
+        }
+        for (map, direction) in [("interfaces", "import"), ("interface_exports", "export")] {
+            for (iface, data) in world.get(map).and_then(|f| f.as_object()).into_iter().flatten() {
+                let iface_path = format!("{}#{}", path, iface);
+                items.push(DocItem {
+                    path: iface_path.clone(),
+                    kind: "interface",
+                    docs: doc_text(data),
+                    container: Some(path.clone()),
+                    direction: Some(direction),
+                });
+                collect_members(data, &format!("{}.", iface_path), &iface_path, Some(direction), &mut items);
+            }
+        }
+    }
+    items
+}
+
+/// Functions (`funcs`) and types, with their fields/cases, of one interface
+/// or world at `path`.
+fn collect_members(
+    container: &Value,
+    prefix: &str,
+    path: &str,
+    direction: Option<&'static str>,
+    items: &mut Vec<DocItem>,
+) {
+    let item = |item_path: String, kind, docs| DocItem {
+        path: item_path,
+        kind,
+        docs,
+        container: Some(path.to_string()),
+        direction,
+    };
+    for (func, data) in container.get("funcs").and_then(|f| f.as_object()).into_iter().flatten() {
+        items.push(item(format!("{}{}", prefix, func), "function", doc_text(data)));
+    }
+    let funcs = container.get("funcs").and_then(|f| f.as_object());
+    let resources: Vec<&str> = funcs.into_iter().flatten().filter_map(|(func, _)| resource_of(func)).collect();
+    for (ty, data) in container.get("types").and_then(|t| t.as_object()).into_iter().flatten() {
+        let ty_path = format!("{}{}", prefix, ty);
+        let kind = if resources.contains(&ty.as_str()) { "resource" } else { "type" };
+        items.push(item(ty_path.clone(), kind, doc_text(data)));
+        for (member, member_docs) in data.get("items").and_then(|i| i.as_object()).into_iter().flatten() {
+            items.push(item(format!("{}.{}", ty_path, member), "member", member_docs.as_str().map(String::from)));
+        }
+    }
+}
+
+/// The resource a function belongs to: `[method]r.m`, `[static]r.m` and
+/// `[constructor]r` => `r`.
+pub fn resource_of(func: &str) -> Option<&str> {
+    let rest = ["[method]", "[static]", "[constructor]"]
+        .iter()
+        .find_map(|prefix| func.strip_prefix(prefix))?;
+    Some(rest.split('.').next().unwrap_or(rest))
+}
+
+/// `func(a: u32) -> string`, as written in WIT: without the implicit `self`
+/// of methods, and `constructor(...)` for constructors.
+pub fn function_signature(resolve: &Resolve, func: &Function) -> String {
+    let (keyword, params) = match func.kind {
+        FunctionKind::Freestanding | FunctionKind::Static(_) => ("func", &func.params[..]),
+        FunctionKind::AsyncFreestanding | FunctionKind::AsyncStatic(_) => ("async func", &func.params[..]),
+        FunctionKind::Method(_) => ("func", func.params.get(1..).unwrap_or_default()),
+        FunctionKind::AsyncMethod(_) => ("async func", func.params.get(1..).unwrap_or_default()),
+        FunctionKind::Constructor(_) => ("constructor", &func.params[..]),
+    };
+    let params: Vec<String> =
+        params.iter().map(|(name, ty)| format!("{}: {}", name, type_name(resolve, ty))).collect();
+    let mut signature = format!("{}({})", keyword, params.join(", "));
+    if let Some(result) = &func.result
+        && !matches!(func.kind, FunctionKind::Constructor(_))
+    {
+        signature.push_str(" -> ");
+        signature.push_str(&type_name(resolve, result));
+    }
+    signature
+}
+
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    let mut printer = WitPrinter::default();
+    match printer.print_type_name(resolve, ty) {
+        Ok(()) => printer.output.to_string(),
+        Err(_) => "_".to_string(),
+    }
+}
+
+/// Where the docs of the item at `path` were written, as recorded in the
+/// `sources` of `package-docs-ext`: the `.wit` file name and line. Items
+/// without a recorded location fall back to the closest item they belong to.
+pub fn source_location<'a>(ext: &'a Value, path: &str) -> Option<(&'a str, usize)> {
+    let sources = ext.get("sources")?.as_object()?;
+    let mut path = path;
+    loop {
+        if let Some((file, line)) = sources.get(path).and_then(|s| s.as_str()?.rsplit_once(':'))
+            && let Ok(line) = line.parse()
+        {
+            return Some((file, line));
+        }
+        path = path.rsplit_once(['.', '#'])?.0;
+    }
+}
+
+/// A GitHub Actions workflow command annotating `file` at `line`, or the
+/// whole run without a location: `::error file=wit/api.wit,line=3,title=...::message`.
+/// `level` is `error`, `warning` or `notice`.
+pub fn github_annotation(level: &str, location: Option<(&Path, usize)>, title: &str, message: &str) -> String {
+    let escape_data = |text: &str| text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+    let escape_property = |text: &str| escape_data(text).replace(':', "%3A").replace(',', "%2C");
+    let mut properties = Vec::new();
+    if let Some((file, line)) = location {
+        let file = file.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        properties.push(format!("file={}", escape_property(&file)));
+        properties.push(format!("line={}", line));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!("::{} {}::{}", level, properties.join(","), escape_data(message))
+}
+
+/// Docs text of an item, accepting both the current `{ "docs": ... }` shape
+/// and the bare strings v0 payloads used for functions.
+pub fn doc_text(item: &Value) -> Option<String> {
+    match item {
+        Value::String(docs) => Some(docs.clone()),
+        _ => item.get("docs").and_then(|d| d.as_str()).map(|s| s.to_string()),
+    }
+}
+
+/// A fenced code block in doc text.
+pub struct CodeBlock {
+    /// Language from the fence's info string (```` ```rust,ignore ```` =>
+    /// `rust`), empty when not given
+    pub lang: String,
+    pub code: String,
+    /// Line of the opening fence within the doc text, 1-based
+    pub line: usize,
+}
+
+/// The fenced (```` ``` ```` or `~~~`) code blocks in `text`. A block left
+/// open runs to the end of the text, as in Markdown.
+pub fn code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    // Fence of the open block and the block so far
+    let mut open: Option<(String, CodeBlock)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let fence_len = |c: char| trimmed.chars().take_while(|&f| f == c).count();
+        let fence = match (fence_len('`'), fence_len('~')) {
+            (n, _) if n >= 3 => Some("`".repeat(n)),
+            (_, n) if n >= 3 => Some("~".repeat(n)),
+            _ => None,
+        };
+        match (&mut open, fence) {
+            (Some((open_fence, _)), Some(fence))
+                if fence.starts_with(open_fence.as_str()) && trimmed[fence.len()..].trim().is_empty() =>
+            {
+                blocks.extend(open.take().map(|(_, block)| block));
+            }
+            (Some((_, block)), _) => {
+                block.code.push_str(line);
+                block.code.push('\n');
+            }
+            (None, Some(fence)) => {
+                let info = trimmed[fence.len()..].trim();
+                let lang = info.split([',', ' ']).next().unwrap_or_default().to_string();
+                open = Some((fence, CodeBlock { lang, code: String::new(), line: index + 1 }));
+            }
+            (None, None) => {}
+        }
+    }
+    blocks.extend(open.map(|(_, block)| block));
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn normalized_names_ignore_escapes_case_and_underscores() {
+        assert_eq!(normalize_name("%record"), "record");
+        assert_eq!(normalize_name("[method]%type.get_ID"), "[method]type.get-id");
+        assert_eq!(normalize_name("wasi:http/Types@0.2.0"), "wasi:http/types@0.2.0");
+    }
+
+    #[test]
+    fn keys_are_found_under_any_spelling() {
+        let docs = map(json!({ "%flags": 1, "get_user": 2, "[static]order.new-id": 3 }));
+        let found = |name: &str| find_key(&docs, name).map(|(key, value)| (key.as_str(), value.clone()));
+        assert_eq!(found("flags"), Some(("%flags", json!(1))));
+        assert_eq!(found("%flags"), Some(("%flags", json!(1))));
+        assert_eq!(found("get-user"), Some(("get_user", json!(2))));
+        assert_eq!(found("[static]order.new_id"), Some(("[static]order.new-id", json!(3))));
+        assert_eq!(found("get-users"), None);
+    }
+
+    #[test]
+    fn exact_keys_come_first() {
+        let docs = map(json!({ "get-user": 1, "get_user": 2 }));
+        assert_eq!(find_key(&docs, "get_user").map(|(_, value)| value), Some(&json!(2)));
+        assert_eq!(find_key(&docs, "get-user").map(|(_, value)| value), Some(&json!(1)));
+    }
+
+    #[test]
+    fn versioned_keys() {
+        let docs = map(json!({ "wasi:http/types@0.2.0": 1 }));
+        assert!(find_key(&docs, "wasi:http/types@0.2.0").is_some());
+        // Versions aren't normalized away: `same_versioned_name` compares them.
+        assert!(find_key(&docs, "wasi:http/types").is_none());
+        assert!(same_versioned_name("wasi:http/types@0.2.0", "wasi:http/types"));
+        assert!(same_versioned_name("wasi:http/types", "wasi:http/types@0.2.0"));
+        assert!(same_versioned_name("wasi:http/%types@0.2.0", "wasi:http/types@0.2.0"));
+        assert!(!same_versioned_name("wasi:http/types@0.2.0", "wasi:http/types@0.3.0"));
+        assert!(!same_versioned_name("wasi:http/types", "wasi:http/handler@0.2.0"));
+    }
+}
@@ -1,22 +1,67 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use std::{borrow::Cow, fs, path::PathBuf};
-use wasm_encoder::{Component, CustomSection};
-use wasm_encoder::reencode::RoundtripReencoder;
-use wasm_encoder::reencode::component_utils::parse_component;
-use wit_parser::{PackageMetadata, Resolve};
-
-/// Inject `package-docs` from a .wit source dir into a component.
+use clap::{ArgGroup, Parser};
+use serde_json::{Map, Value, json};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use wasm_encoder::{ComponentSection, CustomSection};
+use wasmparser::{Encoding, Payload};
+use wit_component::{DecodedWasm, WitPrinter};
+use tracing::{debug, info, info_span, warn};
+use wit_docs_inject::{
+    AtomicFile, COMPOSED_SECTION_NAME, EXT_SECTION_NAME, Packaging, Verbosity, WIT_SECTION_NAME, backup_path,
+    byte_progress, collect_items, decode_package_docs, docs_digest, find_nested_docs, map_file, push_wit_path, spinner,
+    verify_digest,
+};
+use wit_parser::{
+    AstItem, PackageId, PackageMetadata, Resolve, UnresolvedPackageGroup, WorldId, WorldItem,
+    WorldKey,
+};
+
+mod annotations;
+mod cache;
+mod filter;
+mod guest_docs;
+mod js_docs;
+mod metadata;
+mod missing;
+mod patch;
+mod py_docs;
+mod redact;
+mod registry;
+mod roundtrip;
+mod rust_docs;
+mod scope;
+mod sources;
+mod template;
+
+/// Inject `package-docs` from a .wit source dir (or single .wit file) into a component.
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
+#[command(group(ArgGroup::new("docs_source").required(true).args(["wit_dir", "wit_package", "merge_nested"])))]
+// Options for docs read from WIT, which `--merge-nested` doesn't read
+#[command(group(ArgGroup::new("wit_options").multiple(true)))]
 struct Args {
     /// Input component (.wasm) path
     #[arg(long)]
     component: PathBuf,
 
-    /// WIT package dir whose docstrings you want to embed
+    /// WIT package dir, or a single .wit file, whose docstrings you want to embed
+    #[arg(long, visible_alias = "wit")]
+    wit_dir: Option<PathBuf>,
+
+    /// Binary (wasm-encoded) WIT package to take docs from instead of WIT sources
     #[arg(long)]
-    wit_dir: PathBuf,
+    wit_package: Option<PathBuf>,
+
+    /// Instead of reading WIT, gather the docs of the components nested in a
+    /// composed component (e.g. by wac) into one section, keyed by package
+    #[arg(long, conflicts_with = "wit_options")]
+    merge_nested: bool,
 
     /// Output component path (default: in-place overwrite disabled; write alongside with .docs.wasm)
     #[arg(long)]
@@ -25,42 +70,242 @@ struct Args {
     /// Overwrite the input file in place
     #[arg(long, default_value_t = false)]
     inplace: bool,
+
+    /// With `--inplace`, keep the original component as `<component>.bak`
+    #[arg(long, requires = "inplace")]
+    backup: bool,
+
+    /// World the component targets, e.g. `fetch` or `ns:pkg/world@1.0.0`;
+    /// docs are taken from the package that defines it, and only those
+    /// reachable from the world are embedded
+    #[arg(long, group = "wit_options")]
+    world: Option<String>,
+
+    /// With `--world`, embed the docs of the whole package, not only those
+    /// reachable from the world
+    #[arg(long, requires = "world")]
+    whole_package: bool,
+
+    /// Also embed the WIT package (with docs and its dependencies) as text,
+    /// so viewers can show it verbatim without reconstructing it
+    #[arg(long, default_value_t = false, group = "wit_options")]
+    embed_wit: bool,
+
+    /// Directory for caching extracted docs, keyed by a hash of the docs
+    /// source; skips re-parsing unchanged WIT when injecting many components
+    #[arg(long, group = "wit_options")]
+    cache_dir: Option<PathBuf>,
+
+    /// Value for a `{{name}}` variable in doc text, as `name=value`; may be
+    /// repeated. `{{package}}` and `{{version}}` are always defined
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = template::parse_define, group = "wit_options")]
+    defines: Vec<(String, String)>,
+
+    /// JSON Patch (RFC 6902) or merge patch (RFC 7396) to apply to the docs
+    /// model, as shown by `wit-docs-view --format json`, before encoding
+    #[arg(long, group = "wit_options")]
+    patch: Option<PathBuf>,
+
+    /// cargo-component project whose Rust doc comments, on the exported
+    /// `Guest` trait impls, fill in docs missing from the WIT
+    #[arg(long, group = "wit_options")]
+    cargo_component: Option<PathBuf>,
+
+    /// Guest project whose doc comments fill in docs missing from the WIT,
+    /// as `lang=dir` with `lang` one of `rust`, `js` (jco, JavaScript or
+    /// TypeScript) and `python` (componentize-py); may be repeated
+    #[arg(long, value_name = "LANG=DIR", value_parser = guest_docs::parse_guest_docs, group = "wit_options")]
+    guest_docs: Vec<(guest_docs::Lang, PathBuf)>,
+
+    /// Give undocumented exports (functions, and interfaces with their
+    /// functions and types) this text as docs; the stubbed items are listed
+    /// in `package-docs-ext`
+    #[arg(long, value_name = "TEXT", group = "wit_options")]
+    stub_missing: Option<String>,
+
+    /// Command that writes docs for each undocumented export, run by the
+    /// shell with the item's path and signature as JSON on stdin; its
+    /// stdout becomes the docs. With `--cache-dir`, only the command line is
+    /// part of the cache key: after changing the script it runs, clear the
+    /// cache, or cached docs from the old script are reused
+    #[arg(long, value_name = "CMD", group = "wit_options")]
+    generate_missing_cmd: Option<String>,
+
+    /// Keep the paragraphs of docs marked with the internal tag, which are
+    /// otherwise left out of the embedded docs
+    #[arg(long, group = "wit_options")]
+    include_internal: bool,
+
+    /// Marker for paragraphs of docs meant for the package's maintainers
+    /// only, e.g. implementation notes
+    #[arg(long, value_name = "TAG", default_value = redact::DEFAULT_TAG, group = "wit_options")]
+    internal_tag: String,
+
+    /// Only embed the docs of items whose path matches this glob, e.g.
+    /// `wasi:http/*`, or belongs to an item that does; may be repeated
+    #[arg(long, value_name = "GLOB", group = "wit_options")]
+    include: Vec<String>,
+
+    /// Leave out the docs of items whose path matches this glob, e.g.
+    /// `*/internal-*`, or belongs to an item that does; may be repeated
+    #[arg(long, value_name = "GLOB", group = "wit_options")]
+    exclude: Vec<String>,
+
+    /// Build the docs without writing anything, printing the docs given to
+    /// undocumented exports for review
+    #[arg(long, conflicts_with = "cache_dir", group = "wit_options")]
+    dry_run: bool,
+
+    /// Name of the custom section to write the docs to. Toolchains (and
+    /// wit-parser) only read `package-docs`; others are for staging and
+    /// interop. The companion sections keep their names.
+    #[arg(long, value_name = "NAME", default_value = PackageMetadata::SECTION_NAME, group = "wit_options")]
+    section_name: String,
+
+    /// Also write OCI annotations (title, version, description) derived from
+    /// the docs to this file, in the format of `oras push --annotation-file`
+    #[arg(long, value_name = "FILE", group = "wit_options")]
+    annotations_out: Option<PathBuf>,
+
+    /// Read the written component back and fail, leaving the output
+    /// untouched, unless it is the original with only the docs sections
+    /// added: every original section preserved in order, byte for byte
+    #[arg(long)]
+    verify_roundtrip: bool,
+
+    /// Where to insert the new sections: `end` (the default), `start` (right
+    /// after the header), or `after-section NAME` (after the component's
+    /// custom section `NAME`), for tools that expect them at a given point
+    #[arg(long, num_args = 1..=2, value_names = ["WHERE", "NAME"])]
+    position: Vec<String>,
+
+    /// Also write the `registry-metadata` section (authors, description,
+    /// license, links) that `wasm-tools metadata show` and registries read
+    #[arg(long, group = "wit_options")]
+    registry_metadata: bool,
+
+    /// Author of the package, e.g. `Jane Doe <jane@example.com>`; may be
+    /// repeated
+    #[arg(long = "author", value_name = "AUTHOR", group = "wit_options")]
+    authors: Vec<String>,
+
+    /// License of the package, as an SPDX expression, e.g. `Apache-2.0`
+    #[arg(long, value_name = "SPDX", group = "wit_options")]
+    license: Option<String>,
+
+    /// URL of the package's home page
+    #[arg(long, value_name = "URL", group = "wit_options")]
+    homepage: Option<String>,
+
+    /// URL of the package's source repository
+    #[arg(long, value_name = "URL", group = "wit_options")]
+    repository: Option<String>,
+
+    /// Custom metadata to carry along with the docs, as `key=value`, e.g.
+    /// `build-sha=1a2b3c`; may be repeated
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = metadata::parse_meta, group = "wit_options")]
+    meta: Vec<(String, String)>,
+
+    /// File to read package metadata missing from the command line from
+    /// (default: `wit-docs.toml` in the current directory, when present)
+    #[arg(long, value_name = "FILE", group = "wit_options")]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    verbosity: Verbosity,
+
+    #[command(flatten)]
+    packaging: Packaging,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let input = fs::read(&args.component)
-        .with_context(|| format!("reading {:?}", args.component))?;
+    if args.packaging.handle::<Args>(env!("CARGO_BIN_NAME"))? {
+        return Ok(());
+    }
+    args.verbosity.init();
 
-    // 1) Build WIT docs -> binary metadata payload ("package-docs")
-    let mut resolve = Resolve::new();
-    let (pkg_id, _sources) = resolve
-        .push_dir(&args.wit_dir)
-        .with_context(|| format!("parsing WIT dir {:?}", args.wit_dir))?;
-
-    // Extract doc metadata from the WIT package and encode to bytes
-    let meta = PackageMetadata::extract(&resolve, pkg_id);
-    let payload = meta.encode().context("encoding package-docs")?;
+    let read_span = info_span!("read", component = ?args.component).entered();
+    let input = map_file(&args.component)
+        .with_context(|| format!("reading {:?}", args.component))?;
+    check_component(&input).context("reading original component")?;
+    let position = Position::parse(&args.position)?;
+    let split = position.offset(&input).context("placing the docs sections")?;
+    debug!(?position, offset = split, "inserting sections");
+    if args.registry_metadata {
+        registry::check_not_present(&input)?;
+    }
+    debug!(bytes = input.len(), "component is valid");
+    drop(read_span);
 
-    // 2) Reencode component verbatim and append our custom section
-    let mut out_comp = Component::new();
-
-    // Round-trip copy all existing sections exactly.
-    // (This preserves ordering/contents; we only add one extra custom section at the end.)
-    let mut rr = RoundtripReencoder;
-    let parser = wasmparser::Parser::new(0);
-    parse_component(&mut rr, &mut out_comp, parser, &input, &input)
-        .context("reencoding original component")?;
-
-    // Append `package-docs` custom section for components.
-    // Note: SECTION_NAME is "package-docs".
-    let section = CustomSection {
-        name: Cow::Borrowed(PackageMetadata::SECTION_NAME),
-        data: Cow::Owned(payload),
+    // 1) Build WIT docs -> binary metadata payload ("package-docs"), unless
+    // the same sources were already processed. A composed component instead
+    // gets the docs its components already carry.
+    let custom = if args.merge_nested {
+        let _span = info_span!("merge").entered();
+        vec![(COMPOSED_SECTION_NAME, merge_nested_docs(&input)?)]
+    } else {
+        let docs = match &args.cache_dir {
+            Some(cache_dir) => {
+                let key = cache::key(&args).context("hashing WIT sources")?;
+                match cache::load(cache_dir, &key)? {
+                    Some(docs) => {
+                        debug!(%key, "using cached docs");
+                        docs
+                    }
+                    None => {
+                        debug!(%key, "no cached docs");
+                        let docs = build_docs(&args)?;
+                        cache::store(cache_dir, &key, &docs)
+                            .with_context(|| format!("writing cache entry to {:?}", cache_dir))?;
+                        docs
+                    }
+                }
+            }
+            None => build_docs(&args)?,
+        };
+        if args.dry_run {
+            info!("Dry run: nothing written");
+            return Ok(());
+        }
+        if let Some(path) = &args.annotations_out {
+            let annotations = annotations::annotations(&docs.package_docs, &docs.ext)?;
+            let mut file = AtomicFile::create(path).with_context(|| format!("writing {:?}", path))?;
+            writeln!(file.file(), "{}", serde_json::to_string_pretty(&annotations)?)
+                .with_context(|| format!("writing {:?}", path))?;
+            file.commit().with_context(|| format!("replacing {:?}", path))?;
+            info!("Wrote OCI annotations to {:?}", path);
+        }
+        let registry = match args.registry_metadata {
+            true => Some(registry::registry_metadata(&docs.package_docs, &docs.ext)?),
+            false => None,
+        };
+        let mut custom = vec![
+            (args.section_name.as_str(), docs.package_docs),
+            (EXT_SECTION_NAME, docs.ext),
+        ];
+        if let Some(wit) = docs.wit {
+            custom.push((WIT_SECTION_NAME, wit));
+        }
+        if let Some(registry) = registry {
+            custom.push((registry::SECTION_NAME, registry));
+        }
+        custom
     };
-    out_comp.section(&section);
 
-    let bytes = out_comp.finish();
+    // 2) Encode our custom sections. The original component is copied
+    // verbatim and these are inserted at `--position`.
+    let encode_span = info_span!("encode").entered();
+    let mut sections = Vec::new();
+    for (name, data) in &custom {
+        let section = CustomSection {
+            name: Cow::Borrowed(name),
+            data: Cow::Borrowed(data),
+        };
+        debug!(section = name, bytes = data.len(), "encoding custom section");
+        section.append_to_component(&mut sections);
+    }
+    drop(encode_span);
 
     // 3) Write output
     let out_path = if args.inplace {
@@ -78,8 +323,521 @@ fn main() -> Result<()> {
         if out == args.component { out = parent.join(format!("{stem}.docs.injected.wasm")); }
         out
     };
-    fs::write(&out_path, bytes).with_context(|| format!("writing {:?}", out_path))?;
+    let write_span = info_span!("write", out = ?out_path).entered();
+    let same_file = out_path.canonicalize().ok() == args.component.canonicalize().ok();
+    debug!(same_file, "writing output");
+    if args.backup && same_file {
+        let backup = backup_path(&args.component);
+        fs::copy(&args.component, &backup).with_context(|| format!("backing up to {:?}", backup))?;
+        debug!(?backup, "backed up the original component");
+    }
+    // The new component goes to a temporary file that replaces the output
+    // once complete, so the original survives a crash mid-write.
+    let mut out_file = AtomicFile::create(&out_path).with_context(|| format!("writing {:?}", out_path))?;
+    let progress = byte_progress(input.len(), "Writing component");
+    let (before, after) = input.split_at(split);
+    let copy = |file: &mut fs::File, bytes: &[u8]| {
+        bytes.chunks(1 << 20).try_for_each(|chunk| {
+            file.write_all(chunk)?;
+            progress.inc(chunk.len() as u64);
+            Ok::<_, std::io::Error>(())
+        })
+    };
+    copy(out_file.file(), before)
+        .and_then(|()| out_file.file().write_all(&sections))
+        .and_then(|()| copy(out_file.file(), after))
+        .with_context(|| format!("writing {:?}", out_path))?;
+    if args.verify_roundtrip {
+        let _span = info_span!("verify").entered();
+        let written = map_file(out_file.path()).with_context(|| format!("reading back {:?}", out_file.path()))?;
+        roundtrip::verify(&input, &written, &custom, split).context("the written component doesn't round-trip")?;
+        info!("Verified that the output preserves every section of the original component");
+    }
+    // The mapping must go before the file under it is replaced.
+    drop(input);
+    out_file.commit().with_context(|| format!("replacing {:?}", out_path))?;
+    drop(write_span);
+
+    if args.merge_nested {
+        info!("Merged nested package-docs into {:?}", out_path);
+    } else {
+        info!("Injected {} into {:?}", args.section_name, out_path);
+    }
+    Ok(())
+}
+
+/// Where the new sections go in the output, from `--position`.
+#[derive(Debug)]
+enum Position {
+    Start,
+    End,
+    /// After the outermost component's custom section with this name
+    AfterSection(String),
+}
+
+impl Position {
+    fn parse(args: &[String]) -> Result<Self> {
+        match args {
+            [] => Ok(Position::End),
+            [at] if at == "end" => Ok(Position::End),
+            [at] if at == "start" => Ok(Position::Start),
+            [at, name] if at == "after-section" => Ok(Position::AfterSection(name.clone())),
+            [at] if at == "after-section" => anyhow::bail!("--position after-section needs a section name"),
+            _ => anyhow::bail!("--position takes `start`, `end` or `after-section NAME`, not `{}`", args.join(" ")),
+        }
+    }
 
-    eprintln!("Injected package-docs into {:?}", out_path);
+    /// The offset in the component `bytes` to insert the sections at.
+    fn offset(&self, bytes: &[u8]) -> Result<usize> {
+        let name = match self {
+            Position::End => return Ok(bytes.len()),
+            Position::Start => "",
+            Position::AfterSection(name) => name,
+        };
+        // Sections of nested components and modules are out of reach: only
+        // the outermost component's own sections are counted.
+        let mut depth = 0;
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            match payload? {
+                Payload::Version { range, .. } if depth == 0 && matches!(self, Position::Start) => return Ok(range.end),
+                Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+                Payload::End(_) if depth > 0 => depth -= 1,
+                Payload::CustomSection(reader) if depth == 0 && reader.name() == name => return Ok(reader.range().end),
+                _ => {}
+            }
+        }
+        anyhow::bail!("the component has no custom section `{}` to insert after", name)
+    }
+}
+
+/// Fail unless `bytes` is a well-formed component.
+fn check_component(bytes: &[u8]) -> Result<()> {
+    let mut payloads = wasmparser::Parser::new(0).parse_all(bytes);
+    match payloads.next().transpose()? {
+        Some(Payload::Version { encoding: Encoding::Component, .. }) => {}
+        _ => anyhow::bail!("not a WebAssembly component"),
+    }
+    let progress = byte_progress(bytes.len(), "Reading component");
+    for payload in payloads {
+        if let Some((_, range)) = payload?.as_section() {
+            progress.set_position(range.end as u64);
+        }
+    }
     Ok(())
 }
+
+/// The `package-docs-composed` JSON for a composed component:
+/// `{"packages": {"<package>": {"docs": ..., "ext": ...}}}`, with the
+/// `package-docs` payload of each nested component that carries one.
+///
+/// Components injected by this tool are keyed by the package recorded in
+/// their `package-docs-ext`; others by their position, `component-<n>`.
+fn merge_nested_docs(bytes: &[u8]) -> Result<Vec<u8>> {
+    let nested = find_nested_docs(bytes)?;
+    if nested.is_empty() {
+        anyhow::bail!("no nested component carries package-docs");
+    }
+
+    let mut packages = Map::new();
+    for (index, component) in nested.iter().enumerate() {
+        let docs = decode_package_docs(component.package_docs.data)
+            .with_context(|| format!("reading package-docs of nested component {}", index))?
+            .unwrap_or(Value::Null);
+        let ext: Value = match &component.ext {
+            Some(ext) => serde_json::from_slice(ext.data)
+                .with_context(|| format!("reading package-docs-ext of nested component {}", index))?,
+            None => Value::Null,
+        };
+        verify_digest(component.package_docs.data, &ext)
+            .with_context(|| format!("reading package-docs of nested component {}", index))?;
+        let package = match ext.get("package").and_then(|p| p.as_str()) {
+            Some(package) => package.to_string(),
+            None => format!("component-{}", index),
+        };
+        let entry = json!({ "docs": docs, "ext": ext });
+        match packages.get(&package) {
+            // The same package, e.g. shared by several components, once.
+            Some(existing) if *existing == entry => {}
+            Some(_) => warn!("nested components disagree on the docs of {}; keeping the first", package),
+            None => {
+                debug!(%package, index, "merging nested docs");
+                packages.insert(package, entry);
+            }
+        }
+    }
+    Ok(serde_json::to_vec(&json!({ "packages": packages }))?)
+}
+
+/// Contents of the custom sections to append to the component.
+struct DocsSections {
+    /// `package-docs` payload (version byte + JSON)
+    package_docs: Vec<u8>,
+    /// `package-docs-ext` JSON
+    ext: Vec<u8>,
+    /// Printed WIT package, with `--embed-wit`
+    wit: Option<Vec<u8>>,
+}
+
+/// Parse the docs source given in `args` and produce the docs sections.
+fn build_docs(args: &Args) -> Result<DocsSections> {
+    let parse_span = info_span!("parse").entered();
+    let spinner = spinner("Parsing WIT");
+    let mut resolve = Resolve::new();
+    let main_pkg = match (&args.wit_dir, &args.wit_package) {
+        (Some(wit_dir), _) => push_wit_path(&mut resolve, wit_dir)
+            .with_context(|| format!("parsing WIT {:?}", wit_dir))?,
+        (None, Some(wit_package)) => push_wit_package(&mut resolve, wit_package)
+            .with_context(|| format!("decoding WIT package {:?}", wit_package))?,
+        (None, None) => unreachable!("clap requires a docs source"),
+    };
+
+    // A fully-qualified `--world` may name a world from one of the deps, in
+    // which case that package's docs are the ones to embed.
+    let (pkg_id, world_id) = match &args.world {
+        Some(spec) => {
+            let id = resolve
+                .select_world(main_pkg, Some(spec))
+                .with_context(|| format!("selecting world {spec:?}"))?;
+            (resolve.worlds[id].package.unwrap_or(main_pkg), Some(id))
+        }
+        None => (main_pkg, None),
+    };
+    let world = world_id.map(|id| resolve.worlds[id].name.clone());
+    debug!(package = %resolve.packages[pkg_id].name, world = ?world, "docs source resolved");
+
+    // Where each doc comment lives in the sources, for tracing docs back
+    // after publication. wit-parser drops the docs of `use` statements;
+    // they're read back from the sources too.
+    let scan = match &args.wit_dir {
+        Some(wit_dir) if pkg_id == main_pkg => sources::scan(&resolve.packages[pkg_id].name, wit_dir)
+            .context("locating doc comments in WIT sources")?,
+        _ => sources::Scan::default(),
+    };
+    let restored = sources::restore_use_docs(&mut resolve, pkg_id, &scan.use_docs);
+    debug!(restored, "restored docs of use statements");
+    // Authors should know which of their docs won't ship.
+    for lost in sources::lost_docs(&scan, &resolve, pkg_id).context("comparing docs with the sources")? {
+        warn!("{} can't be represented in package-docs and is dropped", lost);
+    }
+    let mut sources = scan.sources;
+
+    // Guest code docs only fill gaps; the WIT has the final say.
+    let projects = guest_projects(args);
+    if !projects.is_empty() {
+        let worlds = match world_id {
+            Some(id) => vec![id],
+            None => resolve.packages[pkg_id].worlds.values().copied().collect(),
+        };
+        for (lang, project) in projects {
+            guest_docs::harvest(lang, &project)
+                .with_context(|| format!("reading guest sources of {:?}", project))?
+                .apply(&mut resolve, &worlds);
+        }
+    }
+
+    template::expand(&mut resolve, pkg_id, &args.defines);
+
+    // Items pulled in via `include` keep their docs on the source world; fill
+    // any gaps and remember where each item came from.
+    // (Binary packages come with includes already elaborated and no record
+    // of them, so origins are only available from sources.)
+    let mut includes = match &args.wit_dir {
+        Some(wit_dir) if pkg_id == main_pkg => resolve_includes(&mut resolve, pkg_id, wit_dir)
+            .context("resolving world includes")?,
+        _ => Map::new(),
+    };
+
+    // Internal notes come out before anything looks for missing docs.
+    let internal_tag = (!args.include_internal).then_some(args.internal_tag.as_str());
+    let redacted = redact::redact(&mut resolve, pkg_id, &mut includes, internal_tag);
+    debug!(redacted, tag = ?internal_tag, "redacted internal docs");
+
+    if let Some(id) = world_id
+        && !args.whole_package
+    {
+        let dropped = scope::scope_to_world(&mut resolve, pkg_id, id);
+        debug!(dropped, "dropped docs unreachable from the world");
+        let name = &resolve.worlds[id].name;
+        includes.retain(|world, _| world == name);
+    }
+
+    // Filtered out items stay out: they aren't given missing docs either.
+    let filter = filter::Filter { include: &args.include, exclude: &args.exclude };
+    if !filter.is_empty() {
+        filter.apply(&mut resolve, pkg_id).context("filtering docs")?;
+    }
+
+    // Docs for what's still undocumented go in last, so that docs from any
+    // other source win: generated ones first, then placeholders.
+    let mut generated = Vec::new();
+    let mut stubbed = Vec::new();
+    if args.generate_missing_cmd.is_some() || args.stub_missing.is_some() {
+        let worlds = match world_id {
+            Some(id) => vec![id],
+            None => resolve.packages[pkg_id].worlds.values().copied().collect(),
+        };
+        let package = resolve.packages[pkg_id].name.to_string();
+        let undocumented = missing::undocumented_exports(&resolve, pkg_id, &worlds);
+        for item in undocumented.into_iter().filter(|item| filter.keeps(&item.path)) {
+            let docs = match &args.generate_missing_cmd {
+                Some(cmd) => missing::generate(cmd, &package, &item)?,
+                None => None,
+            };
+            let (docs, filled) = match (docs, &args.stub_missing) {
+                (Some(docs), _) => (docs, &mut generated),
+                (None, Some(text)) => (text.clone(), &mut stubbed),
+                (None, None) => continue,
+            };
+            if args.dry_run {
+                println!("{}", item.path);
+                docs.lines().for_each(|line| println!("    {}", line));
+                println!();
+            }
+            missing::set_docs(&mut resolve, &item, docs);
+            filled.push(item.path);
+        }
+    }
+    debug!(generated = generated.len(), stubbed = stubbed.len(), "filled undocumented exports");
+
+    // Last-mile edits see the docs exactly as they'd be encoded.
+    if let Some(patch) = &args.patch {
+        patch::apply(&mut resolve, pkg_id, patch).with_context(|| format!("applying patch {:?}", patch))?;
+    }
+
+    debug!(includes = includes.len(), sources = sources.len(), "collected extension data");
+    drop(spinner);
+    drop(parse_span);
+
+    // Extract doc metadata from the WIT package and encode to bytes
+    let _encode_span = info_span!("encode").entered();
+    let meta = PackageMetadata::extract(&resolve, pkg_id);
+    let payload = meta.encode().context("encoding package-docs")?;
+
+    // Locations of docs dropped on the way would point at nothing.
+    let package = resolve.packages[pkg_id].name.to_string();
+    let documented: BTreeSet<String> = collect_items(&serde_json::to_value(&meta)?, Some(&package), None)
+        .into_iter()
+        .filter(|item| item.docs.is_some())
+        .map(|item| item.path)
+        .collect();
+    sources.retain(|path, _| documented.contains(path));
+
+    // The package name lets viewers resolve fully-qualified world names, and
+    // the digest lets them tell the docs weren't damaged on the way.
+    let mut ext = json!({ "package": package, "digest": docs_digest(&payload) });
+    if let Some(world) = world {
+        ext["world"] = Value::String(world);
+    }
+    if !includes.is_empty() {
+        ext["includes"] = Value::Object(includes);
+    }
+    if !sources.is_empty() {
+        ext["sources"] = Value::Object(sources);
+    }
+    if let Some(text) = &args.stub_missing
+        && !stubbed.is_empty()
+    {
+        ext["stubs"] = json!({ "text": text, "items": stubbed });
+    }
+    if !generated.is_empty() {
+        ext["generated"] = json!(generated);
+    }
+    if let Some(metadata) = metadata::metadata(args)? {
+        ext["metadata"] = metadata;
+    }
+    let mut interfaces = world_interfaces(&resolve, pkg_id);
+    if let Some(world) = ext.get("world").and_then(|w| w.as_str())
+        && !args.whole_package
+    {
+        interfaces.retain(|name, _| name == world);
+    }
+    if !interfaces.is_empty() {
+        ext["world_interfaces"] = Value::Object(interfaces);
+    }
+    let ext = serde_json::to_vec(&ext).context("encoding package-docs-ext")?;
+
+    let wit = if args.embed_wit {
+        let others: Vec<PackageId> = resolve.packages.iter().map(|(id, _)| id).filter(|id| *id != pkg_id).collect();
+        let mut printer = WitPrinter::default();
+        printer
+            .print(&resolve, pkg_id, &others)
+            .context("printing WIT package")?;
+        Some(printer.output.to_string().into_bytes())
+    } else {
+        None
+    };
+
+    Ok(DocsSections { package_docs: payload, ext, wit })
+}
+
+/// The guest projects to take docs from: `--cargo-component`, then each
+/// `--guest-docs`.
+fn guest_projects(args: &Args) -> Vec<(guest_docs::Lang, PathBuf)> {
+    let cargo_component = args.cargo_component.iter().map(|project| (guest_docs::Lang::Rust, project.clone()));
+    cargo_component.chain(args.guest_docs.iter().cloned()).collect()
+}
+
+/// Decode a wasm-encoded WIT package, as published by wkg and warg, along with
+/// its dependencies. Docs the package already carries in its own
+/// `package-docs` section are applied during decoding and so get re-extracted.
+fn push_wit_package(resolve: &mut Resolve, path: &Path) -> Result<PackageId> {
+    let bytes = fs::read(path)?;
+    match wit_component::decode(&bytes)? {
+        DecodedWasm::WitPackage(decoded, pkg) => {
+            let remap = resolve.merge(decoded)?;
+            Ok(remap.packages[pkg.index()])
+        }
+        DecodedWasm::Component(..) => {
+            anyhow::bail!("found a component rather than a WIT package; use --wit-dir for WIT sources")
+        }
+    }
+}
+
+/// Walk the `include` statements of every world in the main package.
+///
+/// wit-parser elaborates includes by copying items into the including world
+/// and then forgets the edges, so they're recovered from an unresolved parse
+/// of the same sources. Returns `world -> imports|exports -> item -> origin`,
+/// where the origin records the source world and, for interface imports whose
+/// docs live in a dependency package (and so never reach `package-docs`),
+/// the interface docs.
+fn resolve_includes(resolve: &mut Resolve, pkg_id: PackageId, wit_dir: &Path) -> Result<Map<String, Value>> {
+    let group = UnresolvedPackageGroup::parse_path(wit_dir)?;
+    let unresolved = &group.main;
+    let mut ret = Map::new();
+
+    for (_, world) in unresolved.worlds.iter() {
+        if world.includes.is_empty() {
+            continue;
+        }
+        let Some(&target) = resolve.packages[pkg_id].worlds.get(&world.name) else {
+            continue;
+        };
+
+        let mut origins = Map::new();
+        for ((_, included), renames) in world.includes.iter().zip(&world.include_names) {
+            // Included worlds from other packages are placeholders listed in
+            // `foreign_deps`; local ones carry their own name.
+            let foreign = unresolved.foreign_deps.iter().find_map(|(pkg, items)| {
+                items.iter().find_map(|(name, item)| match item {
+                    AstItem::World(id) if id == included => Some((pkg, name)),
+                    _ => None,
+                })
+            });
+            let (source, origin) = match foreign {
+                Some((pkg, name)) => {
+                    let Some(&dep) = resolve.package_names.get(pkg) else { continue };
+                    let Some(&source) = resolve.packages[dep].worlds.get(name) else { continue };
+                    (source, resolve.id_of_name(dep, name))
+                }
+                None => {
+                    let name = &unresolved.worlds[*included].name;
+                    let Some(&source) = resolve.packages[pkg_id].worlds.get(name) else { continue };
+                    (source, name.clone())
+                }
+            };
+
+            for direction in ["imports", "exports"] {
+                let items = inherit_included_docs(resolve, pkg_id, target, source, direction, renames);
+                let entry = origins
+                    .entry(direction)
+                    .or_insert_with(|| Value::Object(Map::new()));
+                for (name, docs) in items {
+                    let mut origin = json!({ "from": origin });
+                    if let Some(docs) = docs {
+                        origin["docs"] = Value::String(docs);
+                    }
+                    entry[name] = origin;
+                }
+            }
+        }
+        origins.retain(|_, items| items.as_object().is_some_and(|m| !m.is_empty()));
+        if !origins.is_empty() {
+            ret.insert(world.name.clone(), Value::Object(origins));
+        }
+    }
+    Ok(ret)
+}
+
+/// The interfaces each world of the package imports or exports by name
+/// (`import wasi:http/outgoing-handler;`), with their docs: `package-docs`
+/// only has the world's inline items, and the docs of interfaces from
+/// dependency packages don't reach it at all.
+fn world_interfaces(resolve: &Resolve, pkg_id: PackageId) -> Map<String, Value> {
+    let mut ret = Map::new();
+    for (name, &world) in &resolve.packages[pkg_id].worlds {
+        let world = &resolve.worlds[world];
+        let mut directions = Map::new();
+        for (direction, items) in [("imports", &world.imports), ("exports", &world.exports)] {
+            let mut interfaces = Map::new();
+            for (key, item) in items {
+                if let (WorldKey::Interface(_), WorldItem::Interface { id, .. }) = (key, item) {
+                    let mut entry = json!({});
+                    if let Some(docs) = &resolve.interfaces[*id].docs.contents {
+                        entry["docs"] = Value::String(docs.clone());
+                    }
+                    interfaces.insert(resolve.name_world_key(key), entry);
+                }
+            }
+            if !interfaces.is_empty() {
+                directions.insert(direction.to_string(), Value::Object(interfaces));
+            }
+        }
+        if !directions.is_empty() {
+            ret.insert(name.clone(), Value::Object(directions));
+        }
+    }
+    ret
+}
+
+/// Copy docs from `source`'s items onto the matching items of `target` where
+/// the latter has none, returning each included item's printed name along
+/// with docs that `package-docs` can't carry for it.
+fn inherit_included_docs(
+    resolve: &mut Resolve,
+    pkg_id: PackageId,
+    target: WorldId,
+    source: WorldId,
+    direction: &str,
+    renames: &[wit_parser::IncludeName],
+) -> Vec<(String, Option<String>)> {
+    let source_items = match direction {
+        "imports" => resolve.worlds[source].imports.clone(),
+        _ => resolve.worlds[source].exports.clone(),
+    };
+    let mut ret = Vec::new();
+    for (key, item) in source_items {
+        let key = match key {
+            WorldKey::Name(name) => WorldKey::Name(
+                renames
+                    .iter()
+                    .find(|r| r.name == name)
+                    .map(|r| r.as_.clone())
+                    .unwrap_or(name),
+            ),
+            key => key,
+        };
+        let world = &mut resolve.worlds[target];
+        let items = match direction {
+            "imports" => &mut world.imports,
+            _ => &mut world.exports,
+        };
+        let Some(target_item) = items.get_mut(&key) else { continue };
+
+        let mut extra_docs = None;
+        match (target_item, &item) {
+            (WorldItem::Function(f), WorldItem::Function(src)) if f.docs.contents.is_none() => {
+                f.docs.contents = src.docs.contents.clone();
+            }
+            (WorldItem::Interface { id, .. }, _)
+                if matches!(key, WorldKey::Interface(_))
+                    && resolve.interfaces[*id].package != Some(pkg_id) =>
+            {
+                extra_docs = resolve.interfaces[*id].docs.contents.clone();
+            }
+            _ => {}
+        }
+        ret.push((resolve.name_world_key(&key), extra_docs));
+    }
+    ret
+}
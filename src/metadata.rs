@@ -0,0 +1,108 @@
+//! `--author`, `--license`, `--homepage` and `--repository`: metadata about
+//! the package that registries and doc sites show next to its docs, and
+//! `--meta key=value` for anything else an organization wants to carry along
+//! (a build SHA, a support channel, ...).
+//!
+//! The `package-docs` schema has no room for it, so it's recorded in
+//! `package-docs-ext` as `"metadata": {"authors": [...], "license": ...}`,
+//! with the `--meta` pairs under `custom`. Fields not given on the command
+//! line are read from the `[package]` table of `wit-docs.toml`, which takes
+//! the same keys as Cargo.toml's:
+//!
+//! ```toml
+//! [package]
+//! authors = ["Acme Developers <dev@acme.example>"]
+//! license = "Apache-2.0"
+//! homepage = "https://acme.example/shop"
+//! repository = "https://github.com/acme/shop"
+//!
+//! [package.metadata]
+//! support = "#shop-support"
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::Args;
+
+/// Read from the current directory unless `--config` names another file.
+const CONFIG_FILE: &str = "wit-docs.toml";
+
+/// The configuration file to read: `--config`, else `wit-docs.toml` in the
+/// current directory, when there is one.
+pub fn config_path(args: &Args) -> Option<PathBuf> {
+    args.config.clone().or_else(|| Some(PathBuf::from(CONFIG_FILE)).filter(|path| path.is_file()))
+}
+
+/// Parse a `--meta key=value` argument.
+pub fn parse_meta(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(format!("expected `key=value`, got `{}`", arg)),
+    }
+}
+
+/// The `metadata` object of `package-docs-ext`, or `None` when no field is
+/// given.
+pub fn metadata(args: &Args) -> Result<Option<Value>> {
+    let mut metadata = match config_path(args) {
+        Some(path) => load(&path).with_context(|| format!("reading package metadata from {:?}", path))?,
+        None => Map::new(),
+    };
+    if !args.authors.is_empty() {
+        metadata.insert("authors".to_string(), json!(args.authors));
+    }
+    for (key, value) in [("license", &args.license), ("homepage", &args.homepage), ("repository", &args.repository)] {
+        if let Some(value) = value {
+            metadata.insert(key.to_string(), json!(value));
+        }
+    }
+    if !args.meta.is_empty() {
+        let custom = metadata.entry("custom").or_insert_with(|| json!({}));
+        for (key, value) in &args.meta {
+            custom[key] = json!(value);
+        }
+    }
+    Ok((!metadata.is_empty()).then_some(Value::Object(metadata)))
+}
+
+/// The fields of the `[package]` table of the configuration file at `path`.
+fn load(path: &Path) -> Result<Map<String, Value>> {
+    let text = fs::read_to_string(path)?;
+    let table: toml::Table = toml::from_str(&text)?;
+    let mut metadata = Map::new();
+    let Some(package) = table.get("package") else {
+        return Ok(metadata);
+    };
+    let package = package.as_table().context("`package` must be a table")?;
+    for (key, value) in package {
+        match key.as_str() {
+            "authors" => {
+                let authors = value
+                    .as_array()
+                    .and_then(|authors| authors.iter().map(|a| a.as_str()).collect::<Option<Vec<_>>>())
+                    .context("`package.authors` must be a list of strings")?;
+                metadata.insert(key.clone(), json!(authors));
+            }
+            "license" | "homepage" | "repository" => {
+                let value = value.as_str().with_context(|| format!("`package.{}` must be a string", key))?;
+                metadata.insert(key.clone(), json!(value));
+            }
+            "metadata" => {
+                let table = value.as_table().context("`package.metadata` must be a table")?;
+                let mut custom = Map::new();
+                for (key, value) in table {
+                    let value = value.as_str().with_context(|| format!("`package.metadata.{}` must be a string", key))?;
+                    custom.insert(key.clone(), json!(value));
+                }
+                metadata.insert("custom".to_string(), Value::Object(custom));
+            }
+            other => anyhow::bail!("unknown key `package.{}`", other),
+        }
+    }
+    Ok(metadata)
+}
@@ -0,0 +1,173 @@
+//! Docs for undocumented exports: placeholders (`--stub-missing`) and text
+//! from an external command (`--generate-missing-cmd`).
+//!
+//! Both are ordinary docs as far as `package-docs` is concerned; the paths of
+//! the items they were given to are listed in `package-docs-ext`, so that
+//! TODOs and generated text can be told apart from docs written by hand.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Stdio},
+};
+use tracing::debug;
+use wit_docs_inject::{function_signature, is_use, qualify};
+use wit_parser::{Docs, InterfaceId, PackageId, Resolve, TypeDefKind, TypeId, WorldId, WorldItem, WorldKey};
+
+/// An export without docs.
+pub struct Missing {
+    /// Item path, as used by `wit-docs-view`
+    pub path: String,
+    pub kind: &'static str,
+    /// `func(a: u32) -> string`, `interface orders`, `record point`, ...
+    pub signature: String,
+    target: Target,
+}
+
+/// Where the docs of a [`Missing`] item live.
+enum Target {
+    WorldFunction(WorldId, WorldKey),
+    Interface(InterfaceId),
+    Function(InterfaceId, String),
+    Type(TypeId),
+}
+
+/// The undocumented exports of `worlds` that belong to `pkg`: functions, and
+/// interfaces with their functions and types. Interfaces exported by several
+/// worlds are listed once.
+pub fn undocumented_exports(resolve: &Resolve, pkg: PackageId, worlds: &[WorldId]) -> Vec<Missing> {
+    let package = resolve.packages[pkg].name.to_string();
+    let mut missing = Vec::new();
+    let mut seen = Vec::new();
+    for &world in worlds {
+        let world_path = qualify(Some(&package), &resolve.worlds[world].name);
+        for (key, item) in &resolve.worlds[world].exports {
+            match (key, item) {
+                (WorldKey::Name(name), WorldItem::Function(func)) if !has_docs(&func.docs) => missing.push(Missing {
+                    path: format!("{}#{}", world_path, name),
+                    kind: "function",
+                    signature: function_signature(resolve, func),
+                    target: Target::WorldFunction(world, key.clone()),
+                }),
+                // Interfaces declared inline in the world.
+                (WorldKey::Name(name), WorldItem::Interface { id, .. }) => {
+                    let path = format!("{}#{}", world_path, name);
+                    interface(resolve, *id, name, &path, &format!("{}.", path), &mut missing);
+                }
+                // Interfaces of other packages carry their docs elsewhere.
+                (WorldKey::Interface(_), WorldItem::Interface { id, .. })
+                    if resolve.interfaces[*id].package == Some(pkg) && !seen.contains(id) =>
+                {
+                    seen.push(*id);
+                    let Some(name) = &resolve.interfaces[*id].name else { continue };
+                    let path = qualify(Some(&package), name);
+                    interface(resolve, *id, name, &path, &format!("{}#", path), &mut missing);
+                }
+                _ => {}
+            }
+        }
+    }
+    missing
+}
+
+/// The undocumented parts of the interface `id` at `path`: itself, and its
+/// functions and types, named `<prefix><name>`.
+fn interface(resolve: &Resolve, id: InterfaceId, name: &str, path: &str, prefix: &str, missing: &mut Vec<Missing>) {
+    let iface = &resolve.interfaces[id];
+    if !has_docs(&iface.docs) {
+        missing.push(Missing {
+            path: path.to_string(),
+            kind: "interface",
+            signature: format!("interface {}", name),
+            target: Target::Interface(id),
+        });
+    }
+    for (func_name, func) in &iface.functions {
+        if !has_docs(&func.docs) {
+            missing.push(Missing {
+                path: format!("{}{}", prefix, func_name),
+                kind: "function",
+                signature: function_signature(resolve, func),
+                target: Target::Function(id, func_name.clone()),
+            });
+        }
+    }
+    for (ty_name, &ty) in &iface.types {
+        let def = &resolve.types[ty];
+        if is_use(resolve, ty) || has_docs(&def.docs) {
+            continue;
+        }
+        let signature = match def.kind {
+            TypeDefKind::Record(_)
+            | TypeDefKind::Resource
+            | TypeDefKind::Flags(_)
+            | TypeDefKind::Variant(_)
+            | TypeDefKind::Enum(_) => format!("{} {}", def.kind.as_str(), ty_name),
+            _ => format!("type {}", ty_name),
+        };
+        missing.push(Missing {
+            path: format!("{}{}", prefix, ty_name),
+            kind: "type",
+            signature,
+            target: Target::Type(ty),
+        });
+    }
+}
+
+fn has_docs(docs: &Docs) -> bool {
+    docs.contents.as_deref().is_some_and(|d| !d.trim().is_empty())
+}
+
+/// Give `item` the docs `text`.
+pub fn set_docs(resolve: &mut Resolve, item: &Missing, text: String) {
+    let docs = match &item.target {
+        Target::WorldFunction(world, key) => match resolve.worlds[*world].exports.get_mut(key) {
+            Some(WorldItem::Function(func)) => &mut func.docs,
+            _ => return,
+        },
+        Target::Interface(id) => &mut resolve.interfaces[*id].docs,
+        Target::Function(id, name) => match resolve.interfaces[*id].functions.get_mut(name) {
+            Some(func) => &mut func.docs,
+            None => return,
+        },
+        Target::Type(id) => &mut resolve.types[*id].docs,
+    };
+    docs.contents = Some(text);
+}
+
+/// Docs for `item` from `cmd`, run by the shell with
+/// `{"package", "path", "kind", "signature"}` JSON on stdin. Its trimmed
+/// stdout is the docs; empty output leaves the item undocumented.
+pub fn generate(cmd: &str, package: &str, item: &Missing) -> Result<Option<String>> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([flag, cmd])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("running `{}`", cmd))?;
+    let input = json!({
+        "package": package,
+        "path": item.path,
+        "kind": item.kind,
+        "signature": item.signature,
+    });
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Commands are free not to read their input.
+    if let Err(err) = writeln!(stdin, "{}", input)
+        && err.kind() != ErrorKind::BrokenPipe
+    {
+        return Err(err).with_context(|| format!("writing to `{}`", cmd));
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().with_context(|| format!("running `{}`", cmd))?;
+    if !output.status.success() {
+        anyhow::bail!("`{}` failed ({}) for {}", cmd, output.status, item.path);
+    }
+    let text = String::from_utf8(output.stdout).with_context(|| format!("output of `{}` is not UTF-8", cmd))?;
+    let text = text.trim();
+    debug!(path = %item.path, bytes = text.len(), "generated docs");
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
@@ -0,0 +1,46 @@
+//! `--patch`: last-mile edits to the docs model before it's encoded.
+//!
+//! The patch is JSON, either an RFC 6902 JSON Patch (an array of operations)
+//! or an RFC 7396 merge patch (an object), applied to the `package-docs`
+//! model as `wit-docs-view --format json` shows it:
+//!
+//! ```json
+//! [{ "op": "replace", "path": "/interfaces/orders/funcs/place/docs", "value": "Deprecated." }]
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::{fs, path::Path};
+use tracing::debug;
+use wit_parser::{PackageId, PackageMetadata, Resolve};
+
+use crate::template;
+
+/// Apply the patch in `path` to the docs of `pkg`.
+pub fn apply(resolve: &mut Resolve, pkg: PackageId, path: &Path) -> Result<()> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+    let patch: Value = serde_json::from_str(&text).with_context(|| format!("parsing {:?}", path))?;
+
+    let mut model = serde_json::to_value(PackageMetadata::extract(resolve, pkg))?;
+    match patch {
+        Value::Array(_) => {
+            let patch: json_patch::Patch = serde_json::from_value(patch).context("invalid JSON Patch")?;
+            debug!(operations = patch.0.len(), "applying JSON Patch");
+            json_patch::patch(&mut model, &patch)?;
+        }
+        Value::Object(_) => {
+            debug!("applying merge patch");
+            json_patch::merge(&mut model, &patch);
+        }
+        _ => anyhow::bail!("a patch must be a JSON Patch array or a merge patch object"),
+    }
+    let patched: PackageMetadata =
+        serde_json::from_value(model).context("the patched docs don't match the package-docs schema")?;
+
+    // Injecting only sets docs, so clear them first for removals to stick.
+    template::for_each_docs(resolve, pkg, |docs| docs.contents = None);
+    patched
+        .inject(resolve, pkg)
+        .context("the patched docs refer to items the package doesn't have")?;
+    Ok(())
+}
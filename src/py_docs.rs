@@ -0,0 +1,161 @@
+//! Harvest docstrings from the Python sources of a component built with
+//! componentize-py.
+//!
+//! componentize-py has the world's exported functions implemented as methods
+//! of a class deriving from the generated world class (`class
+//! WitWorld(wit_world.WitWorld)`), each exported interface as a class
+//! deriving from `exports.<Interface>`, and each resource of an exported
+//! interface as a class deriving from `exports.<interface>.<Resource>`. The
+//! docstrings of those classes and of their methods are recorded against the
+//! WIT items. Sources are scanned line by line rather than parsed, so base
+//! classes must be written with these qualified names.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::guest_docs::{Adapter, GuestDocs, Target, camel_to_kebab, files_with_extensions, kebab};
+
+/// The Python adapter: every `.py` file in the project.
+pub struct Python;
+
+/// What a class implements.
+struct Implements {
+    target: Target,
+    /// The resource, for classes implementing one
+    resource: Option<String>,
+}
+
+impl Adapter for Python {
+    fn source_files(&self, project: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        files_with_extensions(project, &["py"], &mut files)?;
+        Ok(files)
+    }
+
+    fn scan(&self, _file: &Path, text: &str, docs: &mut GuestDocs) {
+        let lines: Vec<&str> = text.lines().collect();
+        // The class being scanned: its indentation and what it implements.
+        let mut class: Option<(usize, Option<Implements>)> = None;
+        let mut static_method = false;
+
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            if class.as_ref().is_some_and(|(class_indent, _)| indent <= *class_indent) {
+                class = None;
+            }
+
+            if let Some(rest) = trimmed.strip_prefix("class ") {
+                let implements = implements(rest);
+                if let (Some(implements), Some(doc)) = (&implements, docstring(&lines, index)) {
+                    let entry = docs.impls.entry(implements.target.clone()).or_default();
+                    match &implements.resource {
+                        Some(resource) => {
+                            entry.resources.insert(resource.clone(), doc);
+                        }
+                        None => entry.docs = Some(doc),
+                    }
+                }
+                class = Some((indent, implements));
+                continue;
+            }
+
+            let Some((_, Some(implements))) = &class else {
+                continue;
+            };
+            if trimmed.starts_with('@') {
+                static_method |= trimmed.starts_with("@staticmethod");
+                continue;
+            }
+            let def = trimmed.strip_prefix("async ").unwrap_or(trimmed).strip_prefix("def ");
+            let is_static = std::mem::take(&mut static_method);
+            let Some(name) = def.and_then(|rest| rest.split('(').next()).map(str::trim) else {
+                continue;
+            };
+            let key = match &implements.resource {
+                Some(resource) if name == "__init__" => format!("[constructor]{}", resource),
+                _ if name.starts_with('_') => continue,
+                Some(resource) if is_static => format!("[static]{}.{}", resource, kebab(name)),
+                Some(resource) => format!("[method]{}.{}", resource, kebab(name)),
+                None => kebab(name),
+            };
+            if let Some(doc) = docstring(&lines, index) {
+                docs.impls.entry(implements.target.clone()).or_default().funcs.insert(key, doc);
+            }
+        }
+    }
+}
+
+/// What the class declared by `rest` (after `class `) implements, judging by
+/// its base classes.
+fn implements(rest: &str) -> Option<Implements> {
+    let (_, bases) = rest.split_once('(')?;
+    let bases = bases.split(')').next()?;
+    bases.split(',').find_map(|base| {
+        let segments: Vec<&str> = base.trim().split('.').collect();
+        match segments.as_slice() {
+            ["exports", interface] => Some(Implements {
+                target: Target::ExportedInterface(camel_to_kebab(interface)),
+                resource: None,
+            }),
+            ["exports", interface, resource] => Some(Implements {
+                target: Target::ExportedInterface(kebab(interface)),
+                resource: Some(camel_to_kebab(resource)),
+            }),
+            // The generated world class: `wit_world.WitWorld`, `hello.Hello`.
+            [module, class] if module.replace('_', "").eq_ignore_ascii_case(class) => Some(Implements {
+                target: Target::World,
+                resource: None,
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// The docstring of the `class` or `def` starting at `lines[start]`: the
+/// string literal that is the first statement after its header.
+fn docstring(lines: &[&str], start: usize) -> Option<String> {
+    // Headers may span several lines.
+    let header_end =
+        (start..lines.len()).find(|&i| lines[i].split('#').next().unwrap_or_default().trim_end().ends_with(':'))?;
+    let first = lines[header_end + 1..].iter().position(|line| !line.trim().is_empty())? + header_end + 1;
+    let opening = lines[first].trim().trim_start_matches(['r', 'R']);
+    let quote = ["\"\"\"", "'''"].into_iter().find(|q| opening.starts_with(q))?;
+    let body = &opening[3..];
+
+    let mut text = Vec::new();
+    match body.split_once(quote) {
+        Some((line, _)) => text.push(line),
+        None => {
+            text.push(body);
+            for line in &lines[first + 1..] {
+                match line.split_once(quote) {
+                    Some((last, _)) => {
+                        text.push(last);
+                        break;
+                    }
+                    None => text.push(*line),
+                }
+            }
+        }
+    }
+    clean_docstring(&text)
+}
+
+/// `lines` of a docstring without the indentation they share after the
+/// first, as `inspect.cleandoc` does.
+fn clean_docstring(lines: &[&str]) -> Option<String> {
+    let indent = lines[1..]
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut cleaned = vec![lines[0].trim()];
+    cleaned.extend(lines[1..].iter().map(|line| line.get(indent..).unwrap_or("").trim_end()));
+    let text = cleaned.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
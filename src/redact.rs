@@ -0,0 +1,131 @@
+//! Redaction of internal notes from docs.
+//!
+//! A paragraph of docs that carries the internal tag (`@internal` unless
+//! `--internal-tag` says otherwise) is written for the package's maintainers:
+//! it's left out of the embedded docs unless `--include-internal` is given.
+//! Docs made only of such paragraphs are dropped altogether. Fenced code
+//! blocks are never tagged, so examples may show the tag.
+
+use serde_json::{Map, Value};
+use wit_docs_inject::code_blocks;
+use wit_parser::{Docs, PackageId, Resolve};
+
+use crate::template::for_each_docs;
+
+pub const DEFAULT_TAG: &str = "@internal";
+
+/// Remove the paragraphs tagged `tag` from the docs of `pkg` and its items,
+/// and from the docs of included items recorded in `includes`; with no tag
+/// (`--include-internal`), they are all kept. Returns how many docs were
+/// redacted.
+pub fn redact(resolve: &mut Resolve, pkg: PackageId, includes: &mut Map<String, Value>, tag: Option<&str>) -> usize {
+    let Some(tag) = tag else {
+        return 0;
+    };
+    let mut redacted = 0;
+    for_each_docs(resolve, pkg, |docs: &mut Docs| {
+        if let Some(kept) = docs.contents.as_deref().and_then(|text| redact_text(text, tag)) {
+            docs.contents = kept;
+            redacted += 1;
+        }
+    });
+
+    // `{"<world>": {"imports": {"<item>": {"from": ..., "docs": ...}}}}`
+    let directions = includes.values_mut().filter_map(|d| d.as_object_mut());
+    let items = directions.flat_map(|d| d.values_mut()).filter_map(|i| i.as_object_mut());
+    for origin in items.flat_map(|i| i.values_mut()).filter_map(|o| o.as_object_mut()) {
+        let Some(kept) = origin.get("docs").and_then(|d| d.as_str()).and_then(|text| redact_text(text, tag)) else {
+            continue;
+        };
+        match kept {
+            Some(kept) => origin.insert("docs".to_string(), Value::String(kept)),
+            None => origin.remove("docs"),
+        };
+        redacted += 1;
+    }
+    redacted
+}
+
+/// `text` without its paragraphs tagged `tag`: `None` when none is, else
+/// what's left (`Some(None)` when that's nothing).
+fn redact_text(text: &str, tag: &str) -> Option<Option<String>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut in_code = vec![false; lines.len()];
+    for block in code_blocks(text) {
+        // The opening fence, the code and the closing fence
+        let end = block.line + block.code.matches('\n').count() + 1;
+        in_code[block.line - 1..end.min(lines.len())].fill(true);
+    }
+    // Paragraphs and code blocks, whose blank lines don't split them
+    let mut paragraphs: Vec<(bool, Vec<&str>)> = Vec::new();
+    let mut separated = true;
+    for (&line, &code) in lines.iter().zip(&in_code) {
+        if !code && line.trim().is_empty() {
+            separated = true;
+            continue;
+        }
+        match paragraphs.last_mut() {
+            Some((last_code, paragraph)) if !separated && *last_code == code => paragraph.push(line),
+            _ => paragraphs.push((code, vec![line])),
+        }
+        separated = false;
+    }
+    let tagged = |(code, paragraph): &(bool, Vec<&str>)| {
+        !code
+            && paragraph
+                .iter()
+                .flat_map(|line| line.split_whitespace())
+                .any(|word| word.trim_end_matches([':', '.', ',', ';']) == tag)
+    };
+    if !paragraphs.iter().any(tagged) {
+        return None;
+    }
+    let kept: Vec<String> = paragraphs
+        .iter()
+        .filter(|p| !tagged(p))
+        .map(|(_, p)| p.join("\n"))
+        .collect();
+    Some((!kept.is_empty()).then(|| kept.join("\n\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tagged_paragraphs_are_removed() {
+        let text = "Looks up an order.\n\nBacked by the old DB, see @internal.\nTo be replaced.\n\nFails if missing.";
+        let kept = "Looks up an order.\n\nFails if missing.";
+        assert_eq!(redact_text(text, DEFAULT_TAG), Some(Some(kept.to_string())));
+        assert_eq!(redact_text("@internal: all of it.", DEFAULT_TAG), Some(None));
+        assert_eq!(redact_text("Mentions @internals only.", DEFAULT_TAG), None);
+    }
+
+    #[test]
+    fn code_blocks_are_never_tagged() {
+        let text = "Marks notes:\n\n```wit\n/// @internal\n\n/// kept\n```\n\n@internal: dropped.";
+        let kept = "Marks notes:\n\n```wit\n/// @internal\n\n/// kept\n```";
+        assert_eq!(redact_text(text, DEFAULT_TAG), Some(Some(kept.to_string())));
+        assert_eq!(redact_text("~~~\n@internal\n~~~", DEFAULT_TAG), None);
+    }
+
+    #[test]
+    fn the_tag_can_be_changed() {
+        let text = "Public.\n\nTODO(maintainers): rework.\n\n@internal: kept.";
+        assert_eq!(redact_text(text, "TODO(maintainers)"), Some(Some("Public.\n\n@internal: kept.".to_string())));
+    }
+
+    #[test]
+    fn include_internal_keeps_tagged_paragraphs() {
+        let wit = "package acme:shop;\n\n/// Orders.\n///\n/// @internal: uses the legacy DB.\ninterface orders {}";
+        let cases = [(Some(DEFAULT_TAG), "Orders."), (None, "Orders.\n\n@internal: uses the legacy DB.")];
+        for (tag, docs) in cases {
+            let mut resolve = Resolve::default();
+            let pkg = resolve.push_str("shop.wit", wit).unwrap();
+            let redacted = redact(&mut resolve, pkg, &mut Map::new(), tag);
+            assert_eq!(redacted, usize::from(tag.is_some()));
+            let (_, orders) = resolve.interfaces.iter().next().unwrap();
+            assert_eq!(orders.docs.contents.as_deref(), Some(docs));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! `--registry-metadata`: the `registry-metadata` custom section that
+//! `wasm-tools metadata show` and component registries read, derived from
+//! the docs and package metadata being injected:
+//!
+//! ```json
+//! {
+//!   "authors": ["Acme Developers <dev@acme.example>"],
+//!   "description": "Orders, carts and checkout.",
+//!   "license": "Apache-2.0",
+//!   "links": [{ "ty": "Homepage", "value": "https://acme.example/shop" }]
+//! }
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use wit_docs_inject::{decode_package_docs, find_custom_section};
+
+use crate::annotations::description;
+
+/// Name of the section, as wasm-metadata reads it.
+pub const SECTION_NAME: &str = "registry-metadata";
+
+/// The section's JSON for a component injected with the `package-docs`
+/// payload `package_docs` and the `package-docs-ext` JSON `ext`: the
+/// description as in the OCI annotations, and the authors, license, home
+/// page and repository of the package metadata. `--meta` pairs have no place
+/// in it.
+pub fn registry_metadata(package_docs: &[u8], ext: &[u8]) -> Result<Vec<u8>> {
+    let docs = decode_package_docs(package_docs)?.unwrap_or(Value::Null);
+    let ext: Value = serde_json::from_slice(ext).context("reading package-docs-ext")?;
+
+    let mut registry = Map::new();
+    let metadata = &ext["metadata"];
+    if let Some(authors) = metadata.get("authors").and_then(|a| a.as_array()).filter(|a| !a.is_empty()) {
+        registry.insert("authors".to_string(), json!(authors));
+    }
+    if let Some(description) = description(&docs, &ext) {
+        registry.insert("description".to_string(), json!(description));
+    }
+    if let Some(license) = metadata.get("license").and_then(|l| l.as_str()) {
+        registry.insert("license".to_string(), json!(license));
+    }
+    let links: Vec<Value> = [("homepage", "Homepage"), ("repository", "Repository")]
+        .into_iter()
+        .filter_map(|(key, ty)| Some(json!({ "ty": ty, "value": metadata.get(key)?.as_str()? })))
+        .collect();
+    if !links.is_empty() {
+        registry.insert("links".to_string(), json!(links));
+    }
+    Ok(serde_json::to_vec(&registry)?)
+}
+
+/// wasm-metadata rejects a component with two `registry-metadata` sections,
+/// and the injected sections are appended to the original ones.
+pub fn check_not_present(wasm_bytes: &[u8]) -> Result<()> {
+    if find_custom_section(wasm_bytes, SECTION_NAME)?.is_some() {
+        anyhow::bail!(
+            "the component already has a {} section; remove it (`wasm-tools strip --delete {}`) or leave out \
+             --registry-metadata",
+            SECTION_NAME,
+            SECTION_NAME
+        );
+    }
+    Ok(())
+}
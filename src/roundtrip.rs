@@ -0,0 +1,103 @@
+//! `--verify-roundtrip`: read the written component back and check that it
+//! is the original with the docs sections added, and nothing else changed.
+//!
+//! The outermost component's sections are compared one by one, in order and
+//! byte for byte; nested components and modules count as one section each,
+//! so any change inside them shows up too. Signed artifacts stay verifiable
+//! only if this holds.
+
+use anyhow::{Context, Result};
+use std::ops::Range;
+use wasmparser::Payload;
+
+/// A section of the outermost component, as laid out in the binary.
+struct Section<'a> {
+    id: u8,
+    /// Offset of the section in the file
+    offset: usize,
+    /// The whole section: its id, size and contents
+    bytes: &'a [u8],
+    /// For custom sections, their name and data
+    custom: Option<(&'a str, &'a [u8])>,
+}
+
+impl Section<'_> {
+    fn describe(&self) -> String {
+        match self.custom {
+            Some((name, _)) => format!("custom section `{}` at offset {:#x}", name, self.offset),
+            None => format!("section {} at offset {:#x}", self.id, self.offset),
+        }
+    }
+}
+
+/// Check that `output` is `original` with the custom sections `added`
+/// inserted, in order, at byte `offset` of the original, and that it parses.
+pub fn verify(original: &[u8], output: &[u8], added: &[(&str, Vec<u8>)], offset: usize) -> Result<()> {
+    let (original_header, original_sections) = sections(original).context("parsing the original component")?;
+    let (output_header, output_sections) = sections(output).context("parsing the written component")?;
+    if original[original_header] != output[output_header] {
+        anyhow::bail!("the component header changed");
+    }
+    if output_sections.len() != original_sections.len() + added.len() {
+        anyhow::bail!(
+            "the written component has {} sections, expected the original's {} and the {} added",
+            output_sections.len(),
+            original_sections.len(),
+            added.len()
+        );
+    }
+
+    // The added sections are the ones the original had before `offset`.
+    let at = original_sections.iter().take_while(|s| s.offset + s.bytes.len() <= offset).count();
+    let (before, rest) = output_sections.split_at(at);
+    let (inserted, after) = rest.split_at(added.len());
+    for (index, (ours, theirs)) in original_sections.iter().zip(before.iter().chain(after)).enumerate() {
+        if ours.id != theirs.id || ours.bytes != theirs.bytes {
+            anyhow::bail!(
+                "section {} of the original component ({}) isn't preserved: the written component has {} in its place",
+                index,
+                ours.describe(),
+                theirs.describe()
+            );
+        }
+    }
+    for ((name, data), section) in added.iter().zip(inserted) {
+        if section.custom != Some((*name, data.as_slice())) {
+            anyhow::bail!("expected the added custom section `{}`, found {}", name, section.describe());
+        }
+    }
+    Ok(())
+}
+
+/// The header and the sections of the outermost component in `bytes`.
+fn sections(bytes: &[u8]) -> Result<(Range<usize>, Vec<Section<'_>>)> {
+    let mut header = None;
+    let mut sections = Vec::new();
+    let mut depth = 0;
+    // Sections follow each other, so each one runs from the end of the
+    // previous one (or of the header) to the end of its contents.
+    let mut end = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+        let payload = payload?;
+        if depth == 0 {
+            if let Payload::Version { range, .. } = &payload {
+                header = Some(range.clone());
+                end = range.end;
+            }
+            if let Some((id, range)) = payload.as_section() {
+                let custom = match &payload {
+                    Payload::CustomSection(reader) => Some((reader.name(), reader.data())),
+                    _ => None,
+                };
+                sections.push(Section { id, offset: end, bytes: &bytes[end..range.end], custom });
+                end = range.end;
+            }
+        }
+        match payload {
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+            Payload::End(_) if depth > 0 => depth -= 1,
+            _ => {}
+        }
+    }
+    Ok((header.context("no header")?, sections))
+}
@@ -0,0 +1,164 @@
+//! Harvest doc comments from the Rust sources of a cargo-component project.
+//!
+//! wit-bindgen turns each exported interface into a `Guest` trait (and each
+//! exported resource into a `Guest<Resource>` trait) under
+//! `exports::<ns>::<pkg>::<iface>`, and the world's own exported functions
+//! into a `Guest` trait at the root of the bindings. Docs written on the
+//! implementations of those traits are mapped back to the WIT items they
+//! implement, and used wherever the WIT itself has no docs.
+
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use syn::{Attribute, Expr, ImplItem, Item, ItemImpl, Lit, Meta, UseTree};
+use tracing::warn;
+
+use crate::guest_docs::{Adapter, GuestDocs, Target, camel_to_kebab, files_with_extensions, kebab};
+
+/// The Rust adapter: every `.rs` file below `<project>/src`.
+pub struct Rust;
+
+impl Adapter for Rust {
+    fn source_files(&self, project: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        files_with_extensions(&project.join("src"), &["rs"], &mut files)?;
+        Ok(files)
+    }
+
+    fn scan(&self, file: &Path, text: &str, docs: &mut GuestDocs) {
+        match syn::parse_file(text) {
+            Ok(parsed) => {
+                let aliases = use_aliases(&parsed.items);
+                collect_items(&parsed.items, &aliases, docs);
+            }
+            Err(err) => warn!("skipping {:?}, which doesn't parse: {}", file, err),
+        }
+    }
+}
+
+/// Names brought into scope by `use`, mapped to the paths they stand for.
+fn use_aliases(items: &[Item]) -> BTreeMap<String, Vec<String>> {
+    fn walk(tree: &UseTree, prefix: &mut Vec<String>, aliases: &mut BTreeMap<String, Vec<String>>) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                walk(&path.tree, prefix, aliases);
+                prefix.pop();
+            }
+            UseTree::Name(name) => {
+                let mut path = prefix.clone();
+                path.push(name.ident.to_string());
+                aliases.insert(name.ident.to_string(), path);
+            }
+            UseTree::Rename(rename) => {
+                let mut path = prefix.clone();
+                path.push(rename.ident.to_string());
+                aliases.insert(rename.rename.to_string(), path);
+            }
+            UseTree::Group(group) => group.items.iter().for_each(|tree| walk(tree, prefix, aliases)),
+            UseTree::Glob(_) => {}
+        }
+    }
+
+    let mut aliases = BTreeMap::new();
+    for item in items {
+        if let Item::Use(item) = item {
+            walk(&item.tree, &mut Vec::new(), &mut aliases);
+        }
+    }
+    aliases
+}
+
+fn collect_items(items: &[Item], aliases: &BTreeMap<String, Vec<String>>, docs: &mut GuestDocs) {
+    for item in items {
+        match item {
+            Item::Impl(item) => collect_impl(item, aliases, docs),
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_items(items, aliases, docs);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_impl(item: &ItemImpl, aliases: &BTreeMap<String, Vec<String>>, docs: &mut GuestDocs) {
+    let Some((_, path, _)) = &item.trait_ else {
+        return;
+    };
+    let mut segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    if let Some(full) = aliases.get(&segments[0]) {
+        segments.splice(0..1, full.iter().cloned());
+    }
+    let Some(trait_name) = segments.pop() else {
+        return;
+    };
+    let Some(resource) = trait_name.strip_prefix("Guest") else {
+        return;
+    };
+
+    let target = match segments.iter().position(|s| s == "exports") {
+        Some(i) => match &segments[i + 1..] {
+            [name] => Target::NamedInterface(kebab(name)),
+            [namespace, package, interface] => Target::PackageInterface {
+                namespace: kebab(namespace),
+                package: kebab(package),
+                interface: kebab(interface),
+            },
+            _ => return,
+        },
+        None => Target::World,
+    };
+    let entry = docs.impls.entry(target).or_default();
+
+    let resource = (!resource.is_empty()).then(|| camel_to_kebab(resource));
+    let impl_docs = doc_comment(&item.attrs);
+    match (&resource, impl_docs) {
+        (Some(resource), Some(impl_docs)) => {
+            entry.resources.insert(resource.clone(), impl_docs);
+        }
+        (None, Some(impl_docs)) => entry.docs = Some(impl_docs),
+        (_, None) => {}
+    }
+
+    for impl_item in &item.items {
+        let ImplItem::Fn(func) = impl_item else {
+            continue;
+        };
+        let Some(func_docs) = doc_comment(&func.attrs) else {
+            continue;
+        };
+        let name = kebab(&func.sig.ident.to_string());
+        let key = match &resource {
+            None => name,
+            Some(resource) if name == "new" && func.sig.receiver().is_none() => format!("[constructor]{}", resource),
+            Some(resource) if func.sig.receiver().is_none() => format!("[static]{}.{}", resource, name),
+            Some(resource) => format!("[method]{}.{}", resource, name),
+        };
+        entry.funcs.insert(key, func_docs);
+    }
+}
+
+/// The text of the `///` comments (`#[doc]` attributes) in `attrs`.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(s) => Some(s.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
@@ -0,0 +1,62 @@
+//! `--world` scoping: only the docs reachable from the component's world are
+//! embedded, not those of the rest of the package.
+//!
+//! Reachable are the world itself, with its functions and types, and the
+//! interfaces of the package it imports or exports, inline or not. wit-parser
+//! adds the interfaces these `use` types from to the world's imports, so the
+//! types they refer to are reachable too.
+
+use std::collections::HashSet;
+use wit_parser::{Docs, InterfaceId, PackageId, Resolve, TypeDefKind, TypeOwner, WorldId, WorldItem};
+
+/// Drop the docs of everything in `pkg` that isn't reachable from `world`.
+/// Returns how many were dropped.
+pub fn scope_to_world(resolve: &mut Resolve, pkg: PackageId, world: WorldId) -> usize {
+    let target = &resolve.worlds[world];
+    let reachable: HashSet<InterfaceId> = target
+        .imports
+        .values()
+        .chain(target.exports.values())
+        .filter_map(|item| match item {
+            WorldItem::Interface { id, .. } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    let mut dropped = 0;
+    for (_, iface) in resolve.interfaces.iter_mut().filter(|(id, i)| i.package == Some(pkg) && !reachable.contains(id)) {
+        dropped += clear(&mut iface.docs);
+        dropped += iface.functions.values_mut().map(|func| clear(&mut func.docs)).sum::<usize>();
+    }
+    for (_, other) in resolve.worlds.iter_mut().filter(|(id, w)| w.package == Some(pkg) && *id != world) {
+        dropped += clear(&mut other.docs);
+        for item in other.imports.values_mut().chain(other.exports.values_mut()) {
+            if let WorldItem::Function(func) = item {
+                dropped += clear(&mut func.docs);
+            }
+        }
+    }
+    for (_, ty) in resolve.types.iter_mut() {
+        let unreachable = match ty.owner {
+            TypeOwner::Interface(id) => resolve.interfaces[id].package == Some(pkg) && !reachable.contains(&id),
+            TypeOwner::World(id) => resolve.worlds[id].package == Some(pkg) && id != world,
+            TypeOwner::None => false,
+        };
+        if !unreachable {
+            continue;
+        }
+        dropped += clear(&mut ty.docs);
+        dropped += match &mut ty.kind {
+            TypeDefKind::Record(record) => record.fields.iter_mut().map(|field| clear(&mut field.docs)).sum(),
+            TypeDefKind::Variant(variant) => variant.cases.iter_mut().map(|case| clear(&mut case.docs)).sum(),
+            TypeDefKind::Enum(enum_) => enum_.cases.iter_mut().map(|case| clear(&mut case.docs)).sum(),
+            TypeDefKind::Flags(flags) => flags.flags.iter_mut().map(|flag| clear(&mut flag.docs)).sum(),
+            _ => 0,
+        };
+    }
+    dropped
+}
+
+fn clear(docs: &mut Docs) -> usize {
+    usize::from(docs.contents.take().is_some())
+}
@@ -0,0 +1,340 @@
+//! Locate the doc comments of a WIT package in its source files.
+//!
+//! wit-parser keeps no spans once a package is resolved, so the `.wit` text
+//! is scanned directly. Only the structure needed to name documented items is
+//! tracked: top-level interfaces and worlds, their functions, inline
+//! interfaces and types, the fields/cases of those types, and the names
+//! brought in by one-line `use` statements.
+//!
+//! The scan also recovers the doc comments of `use` statements, which
+//! wit-parser drops: the types a `use` brings in are left undocumented.
+//! Doc comments that `package-docs` can't carry are reported by
+//! [`lost_docs`].
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+use wit_docs_inject::collect_items;
+use wit_parser::{Docs, PackageId, PackageMetadata, PackageName, Resolve, WorldItem, WorldKey};
+
+/// What a `{ ... }` block being scanned belongs to.
+enum Scope {
+    /// Members are named `<path>#<name>` (top-level interfaces and worlds)
+    Container { path: String, world: bool },
+    /// Members are named `<path>.<name>` (interfaces declared inside a world)
+    InlineInterface { path: String },
+    /// Fields, cases or flags of the type at `path`
+    Type { path: String },
+    /// Methods of the resource `name` in the container with member prefix `prefix`
+    Resource { prefix: String, name: String },
+    /// Blocks that declare nothing documentable (`include ... with`, nested packages)
+    Other,
+}
+
+/// What scanning a package's sources found, by item path (as used by
+/// `wit-docs-view`).
+#[derive(Default)]
+pub struct Scan {
+    /// `file:line` of the doc comment of every documented item
+    pub sources: Map<String, Value>,
+    /// Doc comments of `use` statements, for each type they bring in
+    pub use_docs: BTreeMap<String, String>,
+    /// `file:line` and code of doc comments on something that isn't an item
+    /// (`import ns:pkg/iface;`, `include`, a closing brace, ...)
+    pub unattached: Vec<(String, String)>,
+}
+
+/// Locate the doc comment of every documented item in the package's `.wit`
+/// files.
+///
+/// `path` is a package directory or a single `.wit` file; files are named
+/// relative to it. `deps/` are not scanned.
+pub fn scan(package: &PackageName, path: &Path) -> Result<Scan> {
+    let files = if path.is_dir() {
+        let mut files: Vec<_> = fs::read_dir(path)
+            .with_context(|| format!("reading {:?}", path))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        files.retain(|p| p.is_file() && p.extension().is_some_and(|e| e == "wit"));
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut scan = Scan::default();
+    for file in files {
+        let text = fs::read_to_string(&file).with_context(|| format!("reading {:?}", file))?;
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        scan_file(package, &name, &text, &mut scan);
+    }
+    Ok(scan)
+}
+
+fn scan_file(package: &PackageName, file: &str, text: &str, scan: &mut Scan) {
+    let mut scopes: Vec<Scope> = Vec::new();
+    let mut doc_line = None;
+    let mut doc_lines: Vec<&str> = Vec::new();
+    let mut in_block_doc = false;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let trimmed = line.trim();
+
+        if in_block_doc {
+            in_block_doc = !trimmed.contains("*/");
+            doc_lines.push(line.split("*/").next().unwrap_or_default().trim_end());
+            continue;
+        }
+        if let Some(doc) = trimmed.strip_prefix("///") {
+            doc_line.get_or_insert(line_no);
+            doc_lines.push(doc.trim_start_matches('/').trim_end());
+            continue;
+        }
+        if let Some(doc) = trimmed.strip_prefix("/**") {
+            doc_line.get_or_insert(line_no);
+            in_block_doc = !doc.contains("*/");
+            doc_lines.push(doc.split("*/").next().unwrap_or_default().trim_end());
+            continue;
+        }
+        // Plain comments and blank lines don't detach docs from their item.
+        let code = trimmed.split("//").next().unwrap_or_default().trim();
+        if code.is_empty() {
+            continue;
+        }
+
+        let lines = std::mem::take(&mut doc_lines);
+        if let (Some(line), Some(names)) = (doc_line, used_names(scopes.last(), code)) {
+            let docs = doc_text(&lines);
+            for path in names {
+                scan.sources.insert(path.clone(), Value::String(format!("{}:{}", file, line)));
+                scan.use_docs.insert(path, docs.clone());
+            }
+        }
+        let item = declared_item(package, scopes.last(), code);
+        match (doc_line.take(), &item) {
+            (Some(line), Some((path, _))) => {
+                scan.sources.insert(path.clone(), Value::String(format!("{}:{}", file, line)));
+            }
+            // Package docs are kept; nested packages aren't this package.
+            (Some(line), None)
+                if !matches!(scopes.last(), Some(Scope::Other))
+                    && !(scopes.is_empty() && split_word(code).0 == "package")
+                    && used_names(scopes.last(), code).is_none() =>
+            {
+                scan.unattached.push((format!("{}:{}", file, line), code.to_string()));
+            }
+            _ => {}
+        }
+
+        let mut opened = item.map(|(_, scope)| scope);
+        for c in code.chars() {
+            match c {
+                '{' => scopes.push(opened.take().unwrap_or(Scope::Other)),
+                '}' => {
+                    scopes.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The path of the item declared by `code`, and the scope a `{` on the same
+/// line would open.
+fn declared_item(package: &PackageName, scope: Option<&Scope>, code: &str) -> Option<(String, Scope)> {
+    match scope {
+        None => {
+            let (keyword, rest) = split_word(code);
+            let path = package.interface_id(&identifier(rest)?);
+            match keyword {
+                "interface" => Some((path.clone(), Scope::Container { path, world: false })),
+                "world" => Some((path.clone(), Scope::Container { path, world: true })),
+                _ => None,
+            }
+        }
+        Some(Scope::Container { path, world }) => {
+            let prefix = format!("{}#", path);
+            let code = if *world { strip_direction(code)? } else { code };
+            member(&prefix, code, *world)
+        }
+        Some(Scope::InlineInterface { path }) => member(&format!("{}.", path), code, false),
+        Some(Scope::Type { path }) => {
+            let path = format!("{}.{}", path, identifier(code)?);
+            Some((path, Scope::Other))
+        }
+        Some(Scope::Resource { prefix, name }) => {
+            let (method, rest) = split_word(code);
+            let key = if method == "constructor" || method.starts_with("constructor(") {
+                format!("[constructor]{}", name)
+            } else if rest.trim_start_matches(':').trim().starts_with("static") {
+                format!("[static]{}.{}", name, identifier(method)?)
+            } else {
+                format!("[method]{}.{}", name, identifier(method)?)
+            };
+            Some((format!("{}{}", prefix, key), Scope::Other))
+        }
+        Some(Scope::Other) => None,
+    }
+}
+
+/// A function, type or (inside worlds) inline interface of a container whose
+/// members are named `<prefix><name>`.
+fn member(prefix: &str, code: &str, world: bool) -> Option<(String, Scope)> {
+    let (keyword, rest) = split_word(code);
+    match keyword {
+        "record" | "variant" | "enum" | "flags" => {
+            let path = format!("{}{}", prefix, identifier(rest)?);
+            Some((path.clone(), Scope::Type { path }))
+        }
+        "resource" => {
+            let name = identifier(rest)?;
+            let scope = Scope::Resource { prefix: prefix.to_string(), name: name.clone() };
+            Some((format!("{}{}", prefix, name), scope))
+        }
+        "type" => Some((format!("{}{}", prefix, identifier(rest)?), Scope::Other)),
+        "use" | "include" => None,
+        _ => {
+            // `name: func(...)` or `name: interface {`, but not `ns:pkg/iface`
+            let (name, ty) = code.split_once(':')?;
+            let path = format!("{}{}", prefix, identifier(name)?);
+            match identifier(ty).as_deref().unwrap_or_default() {
+                "interface" if world => Some((path.clone(), Scope::InlineInterface { path })),
+                "func" | "async" => Some((path, Scope::Other)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// The paths of the types a one-line `use iface.{a, b as c};` in an
+/// interface or world brings in (`<container>#a`, `<container>#c`).
+fn used_names(scope: Option<&Scope>, code: &str) -> Option<Vec<String>> {
+    let prefix = match scope? {
+        Scope::Container { path, .. } => format!("{}#", path),
+        Scope::InlineInterface { path } => format!("{}.", path),
+        _ => return None,
+    };
+    let ("use", rest) = split_word(code) else {
+        return None;
+    };
+    let (_, names) = rest.split_once('{')?;
+    let (names, _) = names.split_once('}')?;
+    let paths = names
+        .split(',')
+        .filter_map(|name| {
+            let name = match name.split_once(" as ") {
+                Some((_, alias)) => alias,
+                None => name,
+            };
+            Some(format!("{}{}", prefix, identifier(name)?))
+        })
+        .collect();
+    Some(paths)
+}
+
+/// Doc comment lines with their common indentation removed, the way
+/// wit-parser joins them.
+fn doc_text(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines.iter().map(|line| line.get(indent..).unwrap_or_default()).collect::<Vec<_>>().join("\n")
+}
+
+/// Give the types brought in by `use` the doc comments of their `use`
+/// statements, unless they already have docs. Returns how many were filled.
+pub fn restore_use_docs(resolve: &mut Resolve, pkg: PackageId, use_docs: &BTreeMap<String, String>) -> usize {
+    let package = &resolve.packages[pkg];
+    let name = &package.name;
+    let mut types = Vec::new();
+    for (iface_name, &iface) in &package.interfaces {
+        let path = name.interface_id(iface_name);
+        types.extend(resolve.interfaces[iface].types.iter().map(|(ty, &id)| (format!("{}#{}", path, ty), id)));
+    }
+    for (world_name, &world) in &package.worlds {
+        let path = name.interface_id(world_name);
+        let world = &resolve.worlds[world];
+        for (key, item) in world.imports.iter().chain(&world.exports) {
+            let WorldKey::Name(item_name) = key else {
+                continue;
+            };
+            match item {
+                WorldItem::Type(id) => types.push((format!("{}#{}", path, item_name), *id)),
+                WorldItem::Interface { id, .. } => types.extend(
+                    resolve.interfaces[*id]
+                        .types
+                        .iter()
+                        .map(|(ty, &ty_id)| (format!("{}#{}.{}", path, item_name, ty), ty_id)),
+                ),
+                WorldItem::Function(_) => {}
+            }
+        }
+    }
+
+    let mut filled = 0;
+    for (path, id) in types {
+        if let Some(text) = use_docs.get(&path)
+            && resolve.types[id].docs.contents.is_none()
+        {
+            resolve.types[id].docs = Docs { contents: Some(text.clone()) };
+            filled += 1;
+        }
+    }
+    filled
+}
+
+/// The doc comments of the sources that don't make it into the
+/// `package-docs` extracted from `resolve`: those on items it has no docs
+/// for, and those on no item at all, as `file:line: what` messages.
+pub fn lost_docs(scan: &Scan, resolve: &Resolve, pkg: PackageId) -> Result<Vec<String>> {
+    let docs = serde_json::to_value(PackageMetadata::extract(resolve, pkg))?;
+    let package = resolve.packages[pkg].name.to_string();
+    let documented: BTreeSet<String> = collect_items(&docs, Some(&package), None)
+        .into_iter()
+        .filter(|item| item.docs.as_deref().is_some_and(|d| !d.trim().is_empty()))
+        .map(|item| item.path)
+        .collect();
+
+    let mut lost: Vec<String> = scan
+        .sources
+        .iter()
+        .filter(|(path, _)| !documented.contains(*path))
+        .map(|(path, location)| format!("{}: the doc comment of {}", location.as_str().unwrap_or_default(), path))
+        .collect();
+    lost.extend(scan.unattached.iter().map(|(location, code)| format!("{}: the doc comment on `{}`", location, code)));
+    Ok(lost)
+}
+
+/// Drop the `import`/`export` keyword of a world item.
+fn strip_direction(code: &str) -> Option<&str> {
+    match split_word(code) {
+        ("import" | "export", rest) => Some(rest),
+        ("use" | "include" | "type" | "record" | "variant" | "enum" | "flags" | "resource", _) => Some(code),
+        _ => None,
+    }
+}
+
+fn split_word(code: &str) -> (&str, &str) {
+    let code = code.trim_start();
+    match code.find(char::is_whitespace) {
+        Some(end) => (&code[..end], code[end..].trim_start()),
+        None => (code, ""),
+    }
+}
+
+/// The (unescaped) identifier at the start of `s`, if any.
+fn identifier(s: &str) -> Option<String> {
+    let s = s.trim_start().trim_start_matches('%');
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(s.len());
+    (end > 0).then(|| s[..end].to_string())
+}
@@ -0,0 +1,165 @@
+//! `{{variable}}` substitution in doc text.
+//!
+//! Docs may refer to `{{package}}` (`ns:pkg`), `{{version}}` (the package
+//! version) and any variable given with `--define name=value`, which take
+//! precedence. References to unknown variables are left as written, and
+//! `\{{name}}` is written as `{{name}}` without being expanded.
+
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, warn};
+use wit_parser::{Docs, PackageId, Resolve, TypeDefKind, TypeOwner, WorldItem};
+
+/// Parse a `--define` argument.
+pub fn parse_define(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+        _ => Err(format!("expected `name=value`, got `{}`", arg)),
+    }
+}
+
+/// Expand the variables in the docs of every item of `pkg`.
+pub fn expand(resolve: &mut Resolve, pkg: PackageId, defines: &[(String, String)]) {
+    let name = &resolve.packages[pkg].name;
+    let mut vars = BTreeMap::new();
+    vars.insert("package".to_string(), format!("{}:{}", name.namespace, name.name));
+    if let Some(version) = &name.version {
+        vars.insert("version".to_string(), version.to_string());
+    }
+    vars.extend(defines.iter().cloned());
+
+    let mut unknown = BTreeSet::new();
+    let mut expanded = 0;
+    for_each_docs(resolve, pkg, |docs: &mut Docs| {
+        if let Some(text) = &docs.contents
+            && text.contains("{{")
+        {
+            docs.contents = Some(substitute(text, &vars, &mut unknown));
+            expanded += 1;
+        }
+    });
+
+    debug!(expanded, "expanded doc variables");
+    for name in unknown {
+        warn!("unknown variable `{{{{{}}}}}` in docs; define it with --define {}=...", name, name);
+    }
+}
+
+/// Call `f` with the docs of `pkg` and of every item in it: worlds,
+/// interfaces (including ones declared inline in worlds), functions, types,
+/// and type members. These are the docs `package-docs` covers.
+pub fn for_each_docs(resolve: &mut Resolve, pkg: PackageId, mut f: impl FnMut(&mut Docs)) {
+    f(&mut resolve.packages[pkg].docs);
+    // Covers interfaces declared inline in the package's worlds too.
+    for (_, iface) in resolve.interfaces.iter_mut().filter(|(_, i)| i.package == Some(pkg)) {
+        f(&mut iface.docs);
+        iface.functions.values_mut().for_each(|func| f(&mut func.docs));
+    }
+    for (_, world) in resolve.worlds.iter_mut().filter(|(_, w)| w.package == Some(pkg)) {
+        f(&mut world.docs);
+        for item in world.imports.values_mut().chain(world.exports.values_mut()) {
+            if let WorldItem::Function(func) = item {
+                f(&mut func.docs);
+            }
+        }
+    }
+    for (_, ty) in resolve.types.iter_mut() {
+        let owned = match ty.owner {
+            TypeOwner::Interface(id) => resolve.interfaces[id].package == Some(pkg),
+            TypeOwner::World(id) => resolve.worlds[id].package == Some(pkg),
+            TypeOwner::None => false,
+        };
+        if !owned {
+            continue;
+        }
+        f(&mut ty.docs);
+        match &mut ty.kind {
+            TypeDefKind::Record(record) => record.fields.iter_mut().for_each(|field| f(&mut field.docs)),
+            TypeDefKind::Variant(variant) => variant.cases.iter_mut().for_each(|case| f(&mut case.docs)),
+            TypeDefKind::Enum(enum_) => enum_.cases.iter_mut().for_each(|case| f(&mut case.docs)),
+            TypeDefKind::Flags(flags) => flags.flags.iter_mut().for_each(|flag| f(&mut flag.docs)),
+            _ => {}
+        }
+    }
+}
+
+/// `text` with each `{{name}}` (spaces around `name` allowed) replaced by
+/// its value, except after a `\`; unknown names are collected in `unknown`.
+fn substitute(text: &str, vars: &BTreeMap<String, String>, unknown: &mut BTreeSet<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        if let Some(before) = rest[..start].strip_suffix('\\') {
+            out.push_str(before);
+            out.push_str("{{");
+            rest = after;
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        match vars.get(name) {
+            Some(value) => out.push_str(value),
+            None => {
+                unknown.insert(name.to_string());
+                out.push_str(&rest[start..start + 2 + end + 2]);
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_text(text: &str) -> (String, BTreeSet<String>) {
+        let vars = BTreeMap::from([
+            ("package".to_string(), "acme:shop".to_string()),
+            ("version".to_string(), "1.2.0".to_string()),
+        ]);
+        let mut unknown = BTreeSet::new();
+        (substitute(text, &vars, &mut unknown), unknown)
+    }
+
+    #[test]
+    fn variables_are_expanded() {
+        let (text, unknown) = expand_text("Since {{package}} {{ version }}.");
+        assert_eq!(text, "Since acme:shop 1.2.0.");
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn unknown_variables_are_left_as_written() {
+        let (text, unknown) = expand_text("See {{ env }}/{{version}} and {{env}}.");
+        assert_eq!(text, "See {{ env }}/1.2.0 and {{env}}.");
+        assert_eq!(unknown, BTreeSet::from(["env".to_string()]));
+    }
+
+    #[test]
+    fn escaped_references_are_not_expanded() {
+        let (text, unknown) = expand_text(r"Write \{{version}} for {{version}}.");
+        assert_eq!(text, "Write {{version}} for 1.2.0.");
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn unclosed_braces_are_left_as_written() {
+        let (text, unknown) = expand_text("{{version}} of {{package");
+        assert_eq!(text, "1.2.0 of {{package");
+        assert!(unknown.is_empty());
+        assert_eq!(expand_text("a {{ b } c").0, "a {{ b } c");
+    }
+
+    #[test]
+    fn defines_split_at_the_first_equals_sign() {
+        assert_eq!(parse_define(" env =a=b"), Ok(("env".to_string(), "a=b".to_string())));
+        assert!(parse_define("=value").is_err());
+        assert!(parse_define("env").is_err());
+    }
+}